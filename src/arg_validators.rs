@@ -1,6 +1,8 @@
 
 use serde_json;
 use input::long_argument;
+use cursor;
+use regex::Regex;
 
 pub fn unsigned_int(v: String) -> Result<(), String> {
     match v.parse::<u64>() {
@@ -26,3 +28,27 @@ pub fn json(value: String) -> Result<(), String> {
             }
     }
 }
+
+pub fn json_object(value: String) -> Result<(), String> {
+    match serde_json::from_str::<serde_json::Value>(&long_argument(&value)?) {
+        Err(err) => Err(format!("JSON is malformed: {}", err)),
+        Ok(ref json_value) if json_value.is_object() => Ok(()),
+        Ok(_) => Err("JSON needs to be an object".to_owned()),
+    }
+}
+
+pub fn cursors(value: String) -> Result<(), String> {
+    cursor::decode_cursors(&long_argument(&value)?).map(|_| ())
+}
+
+pub fn subscription_cursors(value: String) -> Result<(), String> {
+    cursor::decode_subscription_cursors(&long_argument(&value)?).map(|_| ())
+}
+
+pub fn regex(value: String) -> Result<(), String> {
+    Regex::new(&value).map(|_| ()).map_err(|err| format!("Invalid regular expression: {}", err))
+}
+
+pub fn non_negative_int(v: String) -> Result<(), String> {
+    v.parse::<u64>().map(|_| ()).map_err(|_| "Value should be a non-negative integer".to_string())
+}
@@ -1,14 +1,203 @@
 
-use output::{Failure, failure, failure_detailed};
+use output::{Failure, failure, failure_detailed, failure_network, failure_response};
 use std::process::Command;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_core::reactor::Core;
+use hyper::{Client, Method, Request, StatusCode};
+use hyper::header;
+use hyper_tls::HttpsConnector;
+use futures::Future;
+use url::form_urlencoded;
+use serde_json;
+use dirs;
+use http;
 
-/// Executes the zign command
-pub fn zign() -> Result<String, Failure> {
-    let output = Command::new("zign").arg("token").output().map_err(|err| failure_detailed("Failed to run zign command", err))?;
+/// The command of the `--zign` preset
+pub const ZIGN_COMMAND: &str = "zign token";
+
+/// Settings for acquiring tokens by running a command which prints the token on its stdout
+#[derive(Debug, Clone)]
+pub struct TokenCommand {
+    pub command: String,
+    pub cache_ttl: Option<u64>,
+}
+
+/// Runs the token command, unless a token it printed before is cached and hasn't outlived the TTL
+pub fn token_command(token_command: &TokenCommand) -> Result<String, Failure> {
+    let cache_key = format!("command {}", token_command.command);
+    if token_command.cache_ttl.is_some() {
+        if let Some(token) = cached_token(&cache_key, 0) {
+            return Ok(token)
+        }
+    }
+
+    let token = run_token_command(&token_command.command)?;
+
+    if let Some(cache_ttl) = token_command.cache_ttl {
+        cache_token(cache_key, &token, cache_ttl);
+    }
+    Ok(token)
+}
+
+fn run_token_command(command: &str) -> Result<String, Failure> {
+    let output = shell_command(command).output().map_err(|err| failure_detailed(&format!("Failed to run token command `{}`", command), err))?;
     if !output.status.success() {
-        let exit_code = output.status.code().ok_or(failure("zign command was interrupted"))?;
-        let stderr = String::from_utf8(output.stderr).map_err(|err| failure_detailed("Failed to decode zign stderr output as UTF-8", err))?;
-        return Err(failure_detailed(&format!("zign command failed with exit code {}", exit_code), stderr))
+        let exit_code = output.status.code().ok_or(failure(&format!("Token command `{}` was interrupted", command)))?;
+        let stderr = String::from_utf8(output.stderr).map_err(|err| failure_detailed("Failed to decode token command stderr output as UTF-8", err))?;
+        return Err(failure_detailed(&format!("Token command `{}` failed with exit code {}", command, exit_code), stderr))
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|err| failure_detailed("Failed to decode token command stdout output as UTF-8", err))?;
+    let token = stdout.trim();
+    if token.is_empty() {
+        return Err(failure(&format!("Token command `{}` printed no token", command)))
+    }
+    Ok(token.to_owned())
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// Settings for acquiring tokens from an OAuth2 token endpoint using the client credentials grant
+#[derive(Debug, Clone)]
+pub struct OAuth2ClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scope: Option<String>,
+}
+
+/// Seconds before its expiry after which a cached OAuth2 token is not used anymore
+const TOKEN_EXPIRY_MARGIN_SECONDS: u64 = 60;
+
+/// A token as cached on disk
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+/// Gets a token for the client credentials, either from the cache if it's not about to expire, or from the token endpoint
+pub fn oauth2_client_credentials(credentials: &OAuth2ClientCredentials) -> Result<String, Failure> {
+    let cache_key = format!("oauth2 {} {}", credentials.token_url, credentials.client_id);
+    if let Some(token) = cached_token(&cache_key, TOKEN_EXPIRY_MARGIN_SECONDS) {
+        return Ok(token)
     }
-    String::from_utf8(output.stdout).map_err(|err| failure_detailed("Failed to decode zign stdout output as UTF-8", err))
+
+    let response = request_token(credentials.clone())?;
+    cache_token(cache_key, &response.access_token, response.expires_in.unwrap_or(0));
+    Ok(response.access_token)
+}
+
+/// Requests a token from the token endpoint, on a separate thread with its own event loop
+/// since this might be needed while another event loop is running
+fn request_token(credentials: OAuth2ClientCredentials) -> Result<TokenResponse, Failure> {
+    let requesting = thread::spawn(move || -> Result<TokenResponse, Failure> {
+        let mut core = Core::new().map_err(|err| failure_detailed("Failed to initialize HTTP client event loop", err))?;
+        let handle = core.handle();
+        let http_client = Client::configure()
+            .connector(HttpsConnector::new(1, &handle).map_err(|err| failure_detailed("Failed to initialize TLS for HTTPS", err))?)
+            .build(&handle);
+
+        let uri = credentials.token_url.parse().map_err(|err| failure_detailed("Invalid OAuth2 token URL", err))?;
+        let mut request = Request::new(Method::Post, uri);
+        request.headers_mut().set(header::Authorization(header::Basic { username: credentials.client_id, password: Some(credentials.client_secret) }));
+        request.headers_mut().set(header::ContentType::form_url_encoded());
+        let mut form = form_urlencoded::Serializer::new(String::new());
+        form.append_pair("grant_type", "client_credentials");
+        if let Some(ref scope) = credentials.scope {
+            form.append_pair("scope", scope);
+        }
+        request.set_body(form.finish());
+
+        let response = http_client.request(request)
+            .map_err(|err| failure_network("Requesting OAuth2 token failed", err))
+            .and_then(|resp| { let status = resp.status(); http::read_full_resp_body_utf8(resp).map(move |body| (status, body)) });
+
+        match core.run(response)? {
+            (StatusCode::Ok, body) => serde_json::from_str(&body).map_err(|err| failure_detailed("Failed to decode OAuth2 token response", err)),
+            (status, body) => Err(failure_response(&format!("OAuth2 token request failed ({})", status), status, body)),
+        }
+    });
+    requesting.join().unwrap_or_else(|_| Err(failure("OAuth2 token request was interrupted")))
+}
+
+/// A cached token which expires after more than the given margin of seconds
+fn cached_token(cache_key: &str, expiry_margin_seconds: u64) -> Option<String> {
+    load_token_cache().remove(cache_key)
+        .and_then(|token| if token.expires_at > unix_time() + expiry_margin_seconds { Some(token.access_token) } else { None })
+}
+
+/// Caches a token for the given number of seconds
+fn cache_token(cache_key: String, token: &str, expires_in: u64) {
+    let mut cache = load_token_cache();
+    cache.insert(cache_key, CachedToken { access_token: token.to_owned(), expires_at: unix_time() + expires_in });
+    // Failing to cache a token only means it'll be acquired again next time
+    let _ = save_token_cache(&cache);
+}
+
+/// Path of the token cache: `$XDG_CACHE_HOME/nakacli/tokens.json`, defaulting to `~/.cache/nakacli/tokens.json`
+fn token_cache_path() -> Option<PathBuf> {
+    let cache_home = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cache")));
+    cache_home.map(|cache_home| cache_home.join("nakacli").join("tokens.json"))
+}
+
+fn load_token_cache() -> BTreeMap<String, CachedToken> {
+    token_cache_path()
+        .and_then(|path| File::open(path).ok())
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+fn save_token_cache(cache: &BTreeMap<String, CachedToken>) -> Result<(), Failure> {
+    let path = token_cache_path().ok_or(failure("Could not find the home directory to cache tokens in"))?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|err| failure_detailed(&format!("Could not create directory {}", directory.display()), err))?;
+    }
+    let file = private_file(&path).map_err(|err| failure_detailed(&format!("Could not create file {}", path.display()), err))?;
+    serde_json::to_writer(file, cache).map_err(|err| failure_detailed(&format!("Could not write to the file {}", path.display()), err))
+}
+
+/// Creates a file readable by its owner only, since it holds secrets
+#[cfg(unix)]
+pub(crate) fn private_file(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let file = OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // The mode only applies when creating the file, so a file written before is made private too
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn private_file(path: &Path) -> io::Result<File> {
+    File::create(path)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
 }
@@ -0,0 +1,72 @@
+use output::{die_failure, failure_unpublished};
+use serde_json::Value;
+
+/// Publishing outcome of a single event, as reported by Nakadi on 207 and 422 responses
+#[derive(Deserialize, Debug, Clone)]
+pub struct BatchItemResponse {
+    pub eid: Option<String>,
+    pub publishing_status: PublishingStatus,
+    pub step: Option<PublishingStep>,
+    pub detail: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishingStatus {
+    Submitted,
+    Failed,
+    Aborted,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishingStep {
+    None,
+    Validating,
+    Partitioning,
+    Enriching,
+    Publishing,
+}
+
+impl PublishingStatus {
+    fn name(&self) -> &'static str {
+        match *self {
+            PublishingStatus::Submitted => "submitted",
+            PublishingStatus::Failed => "failed",
+            PublishingStatus::Aborted => "aborted",
+        }
+    }
+}
+
+impl PublishingStep {
+    fn name(&self) -> &'static str {
+        match *self {
+            PublishingStep::None => "none",
+            PublishingStep::Validating => "validating",
+            PublishingStep::Partitioning => "partitioning",
+            PublishingStep::Enriching => "enriching",
+            PublishingStep::Publishing => "publishing",
+        }
+    }
+}
+
+/// Reports every event that was not published out of `total` events, then exits with the code of either
+/// a partially or a totally unpublished failure
+pub fn die_with_report(unpublished: &[(usize, BatchItemResponse)], total: usize) -> ! {
+    let headers = ["INDEX", "EID", "STATUS", "STEP", "DETAIL"];
+    let rows: Vec<Vec<String>> = unpublished.iter().map(|&(index, ref item)| vec![
+        index.to_string(),
+        item.eid.clone().unwrap_or("-".to_owned()),
+        item.publishing_status.name().to_owned(),
+        item.step.map(|step| step.name()).unwrap_or("-").to_owned(),
+        item.detail.clone().unwrap_or("-".to_owned()),
+    ]).collect();
+    let items: Vec<Value> = unpublished.iter().map(|&(index, ref item)| json!({
+        "index": index,
+        "eid": item.eid,
+        "publishing_status": item.publishing_status.name(),
+        "step": item.step.map(|step| step.name()),
+        "detail": item.detail,
+    })).collect();
+    die_failure(failure_unpublished(total, &headers, &rows, items))
+}
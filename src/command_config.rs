@@ -0,0 +1,26 @@
+use clap::{App, SubCommand, ArgMatches, AppSettings};
+use command_config_list;
+use command_config_show;
+use command_config_set;
+
+pub const NAME: &str = "config";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME).about("Configuration profiles")
+        .subcommand(command_config_list::sub_command())
+        .subcommand(command_config_show::sub_command())
+        .subcommand(command_config_set::sub_command())
+        .setting(AppSettings::SubcommandRequired)
+}
+
+pub fn run(matches: &ArgMatches) {
+    if matches.subcommand_matches(command_config_list::NAME).is_some() {
+        command_config_list::run()
+    } else if let Some(matches) = matches.subcommand_matches(command_config_show::NAME) {
+        command_config_show::run(matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_config_set::NAME) {
+        command_config_set::run(matches)
+    } else {
+        panic!("No command matched! Should have been caught by clap")
+    }
+}
@@ -0,0 +1,21 @@
+use clap::{App, SubCommand};
+use config;
+use output::{die_failure, die_success};
+
+pub const NAME: &str = "list";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME).about("Lists the names of the configured profiles")
+}
+
+pub fn run() {
+    match config::load() {
+        Ok(config) => {
+            for name in config.profiles.keys() {
+                println!("{}", name);
+            }
+            die_success()
+        },
+        Err(err) => die_failure(err),
+    }
+}
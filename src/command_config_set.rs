@@ -0,0 +1,49 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use config;
+use output::{die_failure, die_success};
+
+pub const NAME:                         &str = "set";
+const ARG_NAME:                         &str = "name";
+const ARG_KEY:                          &str = "key";
+const ARG_VALUE:                        &str = "value";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Sets a setting of a profile, creating the profile if missing")
+        .arg(Arg::with_name(ARG_NAME).index(1).required(true).help("Name of the profile"))
+        .arg(Arg::with_name(ARG_KEY).index(2).required(true).possible_values(config::PROFILE_KEYS).help("The setting"))
+        .arg(Arg::with_name(ARG_VALUE).index(3).required(true).help("Value of the setting"))
+}
+
+struct Params<'a> {
+    name: &'a str,
+    key: &'a str,
+    value: &'a str,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        name: matches.value_of(ARG_NAME).expect("Non-optional argument should have been caught by clap if missing"),
+        key: matches.value_of(ARG_KEY).expect("Non-optional argument should have been caught by clap if missing"),
+        value: matches.value_of(ARG_VALUE).expect("Non-optional argument should have been caught by clap if missing"),
+    }
+}
+
+pub fn run(matches: &ArgMatches) {
+    let params = extract_params(matches);
+
+    let mut config = match config::load() {
+        Ok(config) => config,
+        Err(err) => die_failure(err),
+    };
+
+    if let Err(err) = config.profiles.entry(params.name.to_owned()).or_insert_with(Default::default).set(params.key, params.value) {
+        die_failure(err)
+    }
+
+    match config::save(&config) {
+        Ok(()) => die_success(),
+        Err(err) => die_failure(err),
+    }
+}
@@ -0,0 +1,46 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use config;
+use output::{die_failure, die_success, failure, failure_detailed};
+use toml;
+
+pub const NAME:                         &str = "show";
+const ARG_NAME:                         &str = "name";
+const ARG_REVEAL:                       &str = "reveal";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Shows the settings of a profile")
+        .arg(Arg::with_name(ARG_NAME).default_value(config::DEFAULT_PROFILE).help("Name of the profile"))
+        .arg(Arg::with_name(ARG_REVEAL)
+            .long("reveal")
+            .takes_value(false)
+            .help("Shows the bearer token and the OAuth2 client secret instead of masking them")
+        )
+}
+
+pub fn run(matches: &ArgMatches) {
+    let name = matches.value_of(ARG_NAME).expect("Non-optional argument should have been caught by clap if missing");
+    let reveal = matches.occurrences_of(ARG_REVEAL) > 0;
+
+    let config = match config::load() {
+        Ok(config) => config,
+        Err(err) => die_failure(err),
+    };
+
+    let mut profile = match config.profiles.get(name) {
+        Some(profile) => profile.clone(),
+        None => die_failure(failure(&format!("No profile named {}", name))),
+    };
+    if !reveal {
+        profile.mask_secrets();
+    }
+
+    match toml::to_string(&profile) {
+        Ok(content) => {
+            print!("{}", content);
+            die_success()
+        },
+        Err(err) => die_failure(failure_detailed("Failed to encode profile", err)),
+    }
+}
@@ -3,16 +3,26 @@ use server::ServerInfo;
 use app::Application;
 use serde_json;
 use http;
+use http::HttpClient;
 use hyper::{Method, StatusCode};
 use output;
 use global::*;
 use clap::{ArgMatches, App, SubCommand, Arg};
-use input::long_argument;
+use input::{long_argument, long_argument_lines};
 use uuid::Uuid;
 use chrono::prelude::*;
-use output::{Failure, failure, die_failure};
+use output::{Failure, failure, failure_detailed, failure_response, die_failure, die_success, print_json_value};
 use arg_validators;
 use std::fmt;
+use futures::Future;
+use json_schema;
+use batch_item;
+use batch_item::{BatchItemResponse, PublishingStatus};
+use serde_json::Value;
+use tokio_core::reactor::Timeout;
+use std::time::Duration;
+use rand;
+use rand::Rng;
 
 pub const NAME:             &str = "publish";
 const ARG_EVENT_TYPE:       &str = "event-type";
@@ -22,6 +32,12 @@ const ARG_DATA_UPDATE:      &str = "data-update";
 const ARG_DATA_DELETE:      &str = "data-delete";
 const ARG_DATA_SNAPSHOT:    &str = "data-snapshot";
 const ARG_BUSINESS:         &str = "business";
+const ARG_VALIDATE:         &str = "validate";
+const ARG_NDJSON:           &str = "ndjson";
+const ARG_BATCH_SIZE:       &str = "batch-size";
+const ARG_BATCH_BYTES:      &str = "batch-bytes";
+const ARG_RETRIES:          &str = "retries";
+const ARG_RETRY_BACKOFF:    &str = "retry-backoff";
 
 pub fn sub_command<'a>() -> App<'a, 'a> {
     SubCommand::with_name(NAME)
@@ -73,11 +89,52 @@ pub fn sub_command<'a>() -> App<'a, 'a> {
            .conflicts_with(ARG_DATA_CREATE)
            .conflicts_with(ARG_DATA_DELETE))
         .arg(Arg::with_name(ARG_JSON_BODY)
-            .required(true)
+            .required_unless(ARG_NDJSON)
+            .conflicts_with(ARG_NDJSON)
             .index(2)
             .help("Body of one or more events in JSON format (Use '@' prefix to specify a filepath. e.g. '@event.json')")
             .validator(arg_validators::json)
         )
+        .arg(Arg::with_name(ARG_VALIDATE)
+            .required(false)
+            .takes_value(false)
+            .long("validate")
+            .conflicts_with(ARG_NDJSON)
+            .help("Validate the events against the JSON Schema of the event type before publishing any of them (not supported with --ndjson, whose events are published batch by batch as they're read)"))
+        .arg(Arg::with_name(ARG_NDJSON)
+            .required(false)
+            .takes_value(true)
+            .long("ndjson")
+            .help("Events in newline-delimited JSON format, one object per line, published in batches (Use '@' prefix to specify a filepath, or '@-' to read STDIN)"))
+        .arg(Arg::with_name(ARG_BATCH_SIZE)
+            .required(false)
+            .takes_value(true)
+            .long("batch-size")
+            .default_value("100")
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum number of events published in a single batch when publishing --ndjson events"))
+        .arg(Arg::with_name(ARG_BATCH_BYTES)
+            .required(false)
+            .takes_value(true)
+            .long("batch-bytes")
+            .default_value("1048576")
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum number of bytes of events published in a single batch when publishing --ndjson events"))
+        .arg(Arg::with_name(ARG_RETRIES)
+            .required(false)
+            .takes_value(true)
+            .long("retries")
+            .default_value("0")
+            .validator(arg_validators::non_negative_int)
+            .help("Number of times to retry publishing the events that were not published because of a transient failure"))
+        .arg(Arg::with_name(ARG_RETRY_BACKOFF)
+            .required(false)
+            .takes_value(true)
+            .long("retry-backoff")
+            .value_name("MILLISECONDS")
+            .default_value("100")
+            .validator(arg_validators::unsigned_int)
+            .help("Delay before the first retry, which doubles with every following retry (with random jitter)"))
 }
 
 enum Category {
@@ -107,8 +164,16 @@ impl fmt::Display for DataOp {
 
 struct Params {
     event_type: EventType,
-    json_body: serde_json::Value,
+    events: Events,
     category: Category,
+    validate: bool,
+    retries: u64,
+    retry_backoff: u64,
+}
+
+enum Events {
+    Json(serde_json::Value),
+    Ndjson { source: String, batch_size: usize, batch_bytes: usize },
 }
 
 struct EventType(String);
@@ -130,44 +195,247 @@ fn decode_params(matches: &ArgMatches) -> Params {
             Category::Undefined
         };
 
-    let json_body_str = matches
-        .value_of(ARG_JSON_BODY)
-        .and_then(|v| long_argument(v).ok())
-        .expect("Non-optional argument should have been caught by clap if missing");
+    let events = match matches.value_of(ARG_NDJSON) {
+        Some(source) => Events::Ndjson {
+            source: source.to_owned(),
+            batch_size: matches.value_of(ARG_BATCH_SIZE).and_then(|v| v.parse().ok()).expect("Invalid batch size that should have been caught by clap"),
+            batch_bytes: matches.value_of(ARG_BATCH_BYTES).and_then(|v| v.parse().ok()).expect("Invalid batch bytes that should have been caught by clap"),
+        },
+        None => {
+            let json_body_str = matches
+                .value_of(ARG_JSON_BODY)
+                .and_then(|v| long_argument(v).ok())
+                .expect("Non-optional argument should have been caught by clap if missing");
 
-    let json_body =
-        serde_json::from_str::<serde_json::Value>(&json_body_str).expect("Failed to JSON-decode text that was validated to be JSON by clap");
+            Events::Json(serde_json::from_str::<serde_json::Value>(&json_body_str).expect("Failed to JSON-decode text that was validated to be JSON by clap"))
+        },
+    };
 
     let event_type = EventType(matches.value_of(ARG_EVENT_TYPE).expect("Non-optional argument should have been caught by clap if missing").to_owned());
 
-    Params { event_type, json_body, category }
+    let validate = matches.occurrences_of(ARG_VALIDATE) > 0;
+
+    let retries = matches.value_of(ARG_RETRIES).and_then(|v| v.parse().ok()).expect("Invalid retries that should have been caught by clap");
+    let retry_backoff = matches.value_of(ARG_RETRY_BACKOFF).and_then(|v| v.parse().ok()).expect("Invalid retry backoff that should have been caught by clap");
+
+    Params { event_type, events, category, validate, retries, retry_backoff }
 }
 
 pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
-    let Params { event_type, json_body, category } = decode_params(matches);
+    let Params { event_type, events, category, validate, retries, retry_backoff } = decode_params(matches);
     let server_info = ServerInfo::from_params(global_params);
-    let path = format!("/event-types/{}/events", event_type.0);
 
-    let body_maybe = match category {
-        Category::Undefined     => request_for_undefined(json_body),
-        Category::Data { op }   => request_for_data(&event_type, json_body, op),
-        Category::Business      => request_for_business(json_body),
+    let schema = if validate {
+        let action = fetch_json_schema(&application.http_client, &server_info, &event_type);
+        match application.core.run(action) {
+            Ok(schema)  => Some(schema),
+            Err(err)    => die_failure(err),
+        }
+    } else {
+        None
     };
 
-    let body: serde_json::Value = match body_maybe {
-        Ok(b)       => b,
-        Err(err)    => die_failure(err),    // weird, can't do .unwrap_or_else(die_failure) because ! is not a proper bottom type?
-    };
+    let publisher = Publisher { event_type, category, schema, server_info, retries, retry_backoff };
+
+    match events {
+        Events::Json(json_body) => publisher.publish_json(application, global_params, json_body),
+        Events::Ndjson { source, batch_size, batch_bytes } => publisher.publish_ndjson(application, global_params, &source, batch_size, batch_bytes),
+    }
+}
+
+struct Publisher<'a> {
+    event_type: EventType,
+    category: Category,
+    schema: Option<Value>,
+    server_info: ServerInfo<'a>,
+    retries: u64,
+    retry_backoff: u64,
+}
+
+/// Outcome of publishing a batch of events, after retrying
+enum Outcome {
+    /// Nakadi reported on every event, of which the ones that were not published are paired with their index
+    Reported(Vec<(usize, BatchItemResponse)>),
+    /// Any other response, or the failure to get one
+    Unexpected(Result<(StatusCode, String), Failure>),
+}
 
-    let action = http::execute_and_read_full_resp_body_utf8(
-        &application.http_client,
-        Method::Post,
-        &path,
-        &server_info,
-        Some(&body)
-    );
-    let result = application.core.run(action);
-    output::final_result(result, StatusCode::Ok, global_params.pretty)
+/// Counts of the events published so far out of multiple batches
+#[derive(Default)]
+struct Summary {
+    published: usize,
+    batches: usize,
+    unpublished: Vec<(usize, BatchItemResponse)>,
+}
+
+impl Summary {
+    fn print(&self, pretty: bool) {
+        print_json_value(&json!({ "published": self.published, "unpublished": self.unpublished.len(), "batches": self.batches }), pretty)
+    }
+}
+
+impl<'a> Publisher<'a> {
+
+    fn envelope(&self, json_body: Value, first_index: usize) -> Result<Value, Failure> {
+        let body = match self.category {
+            Category::Undefined     => request_for_undefined(json_body),
+            Category::Data { op }   => request_for_data(&self.event_type, json_body, op),
+            Category::Business      => request_for_business(json_body),
+        }?;
+        if let Some(ref schema) = self.schema {
+            validate_events(schema, &body, &self.category, first_index)?;
+        }
+        Ok(body)
+    }
+
+    fn send(&self, application: &mut Application, body: &Value) -> Result<(StatusCode, String), Failure> {
+        let path = format!("/event-types/{}/events", self.event_type.0);
+        let action = http::execute_and_read_full_resp_body_utf8(
+            &application.http_client,
+            Method::Post,
+            &path,
+            &self.server_info,
+            Some(body)
+        );
+        application.core.run(action)
+    }
+
+    /// Publishes enveloped events, retrying the ones that were not published because of a transient failure.
+    ///
+    /// Retried events are sent as they were first enveloped, keeping their `eid`s.
+    fn publish(&self, application: &mut Application, body: Value, first_index: usize) -> Outcome {
+        let events = match body {
+            Value::Array(events) => events,
+            event => vec![event],
+        };
+        let mut pending: Vec<(usize, Value)> = events.into_iter().enumerate().map(|(index, event)| (first_index + index, event)).collect();
+        let mut attempt = 0;
+
+        loop {
+            let body = Value::Array(pending.iter().map(|&(_, ref event)| event.clone()).collect());
+            let can_retry = attempt < self.retries;
+
+            match self.send(application, &body) {
+                Ok((StatusCode::Ok, _)) => return Outcome::Reported(vec![]),
+                Ok((status_code @ StatusCode::MultiStatus, ref response)) | Ok((status_code @ StatusCode::UnprocessableEntity, ref response)) => {
+                    let items = match serde_json::from_str::<Vec<BatchItemResponse>>(response) {
+                        Ok(items)   => items,
+                        // e.g. a problem document explaining why the whole batch was rejected
+                        Err(_)      => return Outcome::Unexpected(Ok((status_code, response.clone()))),
+                    };
+                    let unpublished: Vec<((usize, Value), BatchItemResponse)> = pending.into_iter().zip(items)
+                        .filter(|&(_, ref item)| item.publishing_status != PublishingStatus::Submitted)
+                        .collect();
+                    // Events failing validation, enrichment or partitioning would only fail again
+                    if status_code == StatusCode::UnprocessableEntity || !can_retry {
+                        return Outcome::Reported(unpublished.into_iter().map(|((index, _), item)| (index, item)).collect())
+                    }
+                    pending = unpublished.into_iter().map(|(pending_event, _)| pending_event).collect();
+                },
+                Ok((status_code, _)) if can_retry && (status_code == StatusCode::TooManyRequests || status_code.is_server_error()) => (),
+                Err(_) if can_retry => (),
+                result => return Outcome::Unexpected(result),
+            }
+
+            self.back_off(application, attempt);
+            attempt += 1;
+        }
+    }
+
+    /// Waits exponentially longer before every retry, with random jitter to avoid retrying in lockstep
+    fn back_off(&self, application: &mut Application, attempt: u64) {
+        let delay = self.retry_backoff.saturating_mul(1 << attempt.min(16));
+        let jittered = delay / 2 + rand::thread_rng().gen_range(0, delay / 2 + 1);
+        let timeout = Timeout::new(Duration::from_millis(jittered), &application.core.handle()).expect("Failed to schedule retry");
+        application.core.run(timeout).expect("Failed to wait before retrying");
+    }
+
+    fn publish_json(&self, application: &mut Application, global_params: &GlobalParams, json_body: Value) {
+        let body: Value = match self.envelope(json_body, 0) {
+            Ok(b)       => b,
+            Err(err)    => die_failure(err),    // weird, can't do .unwrap_or_else(die_failure) because ! is not a proper bottom type?
+        };
+
+        let total = body.as_array().map_or(0, |events| events.len());
+        match self.publish(application, body, 0) {
+            Outcome::Reported(ref unpublished) if unpublished.is_empty() => die_success(),
+            Outcome::Reported(unpublished) => batch_item::die_with_report(&unpublished, total),
+            Outcome::Unexpected(result) => output::final_result(result, StatusCode::Ok, global_params.pretty),
+        }
+    }
+
+    /// Publishes newline-delimited events in sequential batches of up to `batch_size` events and `batch_bytes` bytes
+    fn publish_ndjson(&self, application: &mut Application, global_params: &GlobalParams, source: &str, batch_size: usize, batch_bytes: usize) -> ! {
+        let lines = match long_argument_lines(source) {
+            Ok(lines)   => lines,
+            Err(err)    => die_failure(failure(&err)),
+        };
+
+        let mut summary = Summary::default();
+        let mut batch: Vec<Value> = vec![];
+        let mut batch_first_index = 0;
+        let mut batch_length = 0;
+
+        for (line_number, line) in lines.enumerate().map(|(index, line)| (index + 1, line)) {
+            let line = match line {
+                Ok(line)    => line,
+                Err(err)    => self.die_midway(&summary, failure(&err), global_params.pretty),
+            };
+            if line.trim().is_empty() {
+                continue
+            }
+            let event = match serde_json::from_str::<Value>(&line) {
+                Ok(event @ Value::Object(_))    => event,
+                Ok(_)                           => self.die_midway(&summary, failure(&format!("Line {} is not a JSON object", line_number)), global_params.pretty),
+                Err(err)                        => self.die_midway(&summary, failure_detailed(&format!("Line {} is malformed", line_number), err), global_params.pretty),
+            };
+
+            if !batch.is_empty() && (batch.len() >= batch_size || batch_length + line.len() > batch_bytes) {
+                let full_batch = ::std::mem::replace(&mut batch, vec![]);
+                self.publish_batch(application, global_params, full_batch, batch_first_index, &mut summary);
+                batch_first_index = summary.published + summary.unpublished.len();
+                batch_length = 0;
+            }
+            batch_length += line.len();
+            batch.push(event);
+        }
+
+        if !batch.is_empty() {
+            self.publish_batch(application, global_params, batch, batch_first_index, &mut summary);
+        }
+
+        summary.print(global_params.pretty);
+        if summary.unpublished.is_empty() {
+            die_success()
+        } else {
+            batch_item::die_with_report(&summary.unpublished, summary.published + summary.unpublished.len())
+        }
+    }
+
+    fn publish_batch(&self, application: &mut Application, global_params: &GlobalParams, events: Vec<Value>, first_index: usize, summary: &mut Summary) {
+        let count = events.len();
+        let body = match self.envelope(Value::Array(events), first_index) {
+            Ok(body)    => body,
+            Err(err)    => self.die_midway(summary, err, global_params.pretty),
+        };
+
+        match self.publish(application, body, first_index) {
+            Outcome::Reported(unpublished) => {
+                summary.published += count - unpublished.len();
+                summary.unpublished.extend(unpublished);
+            },
+            Outcome::Unexpected(Ok((status_code, response))) =>
+                self.die_midway(summary, failure_response(&format!("Unexpected response ({})", status_code), status_code, response), global_params.pretty),
+            Outcome::Unexpected(Err(err)) => self.die_midway(summary, err, global_params.pretty),
+        }
+        summary.batches += 1;
+    }
+
+    /// Prints what was published so far before failing
+    fn die_midway(&self, summary: &Summary, failure: Failure, pretty: bool) -> ! {
+        summary.print(pretty);
+        die_failure(failure)
+    }
 }
 
 fn request_for_business(json_body: serde_json::Value) -> Result<serde_json::Value, Failure> {
@@ -231,3 +499,46 @@ fn request_for_data(event_type: &EventType ,json_body: serde_json::Value, op: Da
     }
 }
 
+
+/// Fetches the JSON Schema of an event type, which Nakadi keeps encoded as a string
+fn fetch_json_schema<'a>(http_client: &'a HttpClient, server_info: &'a ServerInfo<'a>, event_type: &EventType) -> impl Future<Item=serde_json::Value, Error=Failure> + 'a {
+    let name = event_type.0.clone();
+    http::get_json::<serde_json::Value>(http_client, &format!("/event-types/{}", name), server_info)
+        .and_then(move |definition| {
+            let schema = definition["schema"]["schema"].as_str()
+                .ok_or(failure(&format!("Event type {} has no JSON Schema to validate against", name)))?;
+            match serde_json::from_str(schema) {
+                Ok(schema @ serde_json::Value::Object(_)) => Ok(schema),
+                Ok(_) => Err(failure("JSON Schema of the event type is not an object")),
+                Err(err) => Err(failure_detailed("Failed to decode JSON Schema of the event type", err)),
+            }
+        })
+}
+
+/// Validates every enveloped event of the request body against the JSON Schema of the event type.
+///
+/// Violations are reported with JSON Pointers into the request body, with the events indexed from `first_index`.
+fn validate_events(schema: &serde_json::Value, body: &serde_json::Value, category: &Category, first_index: usize) -> Result<(), Failure> {
+    let events = body.as_array().map(|events| events.as_slice()).unwrap_or(&[]);
+    let violations: Vec<String> = events.iter().enumerate().map(|(index, event)| (first_index + index, event)).flat_map(|(index, event)| {
+        let (prefix, validated) = match *category {
+            Category::Undefined => (format!("/{}", index), event.clone()),
+            Category::Data { .. } => (format!("/{}/data", index), event["data"].clone()),
+            Category::Business => {
+                let mut without_metadata = event.clone();
+                if let Some(fields) = without_metadata.as_object_mut() {
+                    fields.remove("metadata");
+                }
+                (format!("/{}", index), without_metadata)
+            },
+        };
+        json_schema::validate(schema, &validated).into_iter()
+            .map(move |error| format!("{}{}: {}", prefix, error.path, error.message))
+    }).collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(failure_detailed("Events do not match the JSON Schema of the event type", format!("\n{}", violations.join("\n"))))
+    }
+}
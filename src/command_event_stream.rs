@@ -1,30 +1,64 @@
 use http::*;
 use futures::future;
+use futures::future::Either;
 use server::ServerInfo;
 use futures::Future;
 use app::Application;
 use hyper::{Method, Response, StatusCode};
-use output::{die_failure, failure_detailed, print_json_value, Failure, die_success, failure};
+use output::{die_failure, failure_detailed, print_json_value, require_streamable_output_format, Failure, die_success, failure};
 use futures::Stream;
-use serde_json::{Value, Map, from_str};
+use serde_json::{Value, Map, from_str, to_string};
 use global::*;
 use clap::{ArgMatches, App, SubCommand, Arg};
-use futures::stream;
 use arg_validators;
+use cursor;
+use cursor::Cursor;
+use input::long_argument;
+use partition;
 
 pub const NAME: &str = "stream";
 const ARG_EVENT_TYPE: &str = "event-type";
 const ARG_TAKE: &str = "take";
+const ARG_FROM: &str = "from";
+const ARG_FROM_VALUES: &[&str] = &["end", "begin"];
+const ARG_CURSORS: &str = "cursors";
+const ARG_BATCH_LIMIT: &str = "batch-limit";
+const ARG_STREAM_LIMIT: &str = "stream-limit";
+const ARG_BATCH_FLUSH_TIMEOUT: &str = "batch-flush-timeout";
+const ARG_STREAM_TIMEOUT: &str = "stream-timeout";
+const ARG_STREAM_KEEP_ALIVE_LIMIT: &str = "stream-keep-alive-limit";
+
+/// Stream tuning arguments, and the query parameters they're sent as
+const STREAM_PARAMETERS: &[(&str, &str)] = &[
+    (ARG_BATCH_LIMIT, "batch_limit"),
+    (ARG_STREAM_LIMIT, "stream_limit"),
+    (ARG_BATCH_FLUSH_TIMEOUT, "batch_flush_timeout"),
+    (ARG_STREAM_TIMEOUT, "stream_timeout"),
+    (ARG_STREAM_KEEP_ALIVE_LIMIT, "stream_keep_alive_limit"),
+];
+
+/// Stream tuning arguments that make the server end the stream
+const STREAM_ENDING_ARGS: &[&str] = &[ARG_STREAM_LIMIT, ARG_STREAM_TIMEOUT, ARG_STREAM_KEEP_ALIVE_LIMIT];
 
 struct Params<'a> {
     event_type: &'a str,
     take: Option<usize>,
+    from_begin: bool,
+    cursors: Option<Vec<Cursor>>,
+    stream_parameters: Vec<(&'a str, &'a str)>,
+    ends_cleanly: bool,
 }
 
 fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
     Params {
         event_type: matches.value_of(ARG_EVENT_TYPE).expect("Non-optional argument should have been caught by clap if missing"),
         take: matches.value_of(ARG_TAKE).and_then(|v| v.parse().ok()),
+        from_begin: matches.value_of(ARG_FROM) == Some("begin"),
+        cursors: matches.value_of(ARG_CURSORS)
+            .and_then(|v| long_argument(v).ok())
+            .map(|v| cursor::decode_cursors(&v).expect("Failed to decode cursors that were validated by clap")),
+        stream_parameters: STREAM_PARAMETERS.iter().filter_map(|&(arg, query_name)| matches.value_of(arg).map(|v| (query_name, v))).collect(),
+        ends_cleanly: STREAM_ENDING_ARGS.iter().any(|arg| matches.is_present(arg)),
     }
 }
 
@@ -33,84 +67,125 @@ pub fn sub_command<'a>() -> App<'a, 'a> {
         .about("Stream-listen on published events")
         .arg(Arg::with_name(ARG_EVENT_TYPE).required(true).index(1).help("Name of the Event Type"))
         .arg(Arg::with_name(ARG_TAKE).long("take").short("n").takes_value(true).value_name("N").help("Exits after consuming N events from the stream").validator(arg_validators::unsigned_int))
+        .arg(Arg::with_name(ARG_FROM)
+            .long("from")
+            .takes_value(true)
+            .possible_values(ARG_FROM_VALUES)
+            .conflicts_with(ARG_CURSORS)
+            .help("Starts streaming from either the oldest or the newest available events of all the partitions. 'end' is the same as not passing --from at all"))
+        .arg(Arg::with_name(ARG_CURSORS)
+            .long("cursors")
+            .takes_value(true)
+            .validator(arg_validators::cursors)
+            .help("JSON array of the cursors to start streaming after (Use '@' prefix to specify a filepath. e.g. '@cursors.json')"))
+        .arg(Arg::with_name(ARG_BATCH_LIMIT)
+            .long("batch-limit")
+            .takes_value(true)
+            .value_name("N")
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum number of events in each batch of the stream"))
+        .arg(Arg::with_name(ARG_STREAM_LIMIT)
+            .long("stream-limit")
+            .takes_value(true)
+            .value_name("N")
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum number of events in the stream, after which the server ends it"))
+        .arg(Arg::with_name(ARG_BATCH_FLUSH_TIMEOUT)
+            .long("batch-flush-timeout")
+            .takes_value(true)
+            .value_name("SECONDS")
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum time to wait for a batch to fill up before the server flushes it"))
+        .arg(Arg::with_name(ARG_STREAM_TIMEOUT)
+            .long("stream-timeout")
+            .takes_value(true)
+            .value_name("SECONDS")
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum time the stream stays open, after which the server ends it"))
+        .arg(Arg::with_name(ARG_STREAM_KEEP_ALIVE_LIMIT)
+            .long("stream-keep-alive-limit")
+            .takes_value(true)
+            .value_name("N")
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum number of consecutive batches without events, after which the server ends the stream"))
 }
 
 pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+    require_streamable_output_format();
     let params = extract_params(matches);
     let server_info = ServerInfo::from_params(global_params);
 
-    let path = format!("/event-types/{}/events", params.event_type);
-    let method = Method::Get;
-    let body = None;
-    let request = build_request(method, &path, &server_info, body);
+    let path = path_with_query(&format!("/event-types/{}/events", params.event_type), &params.stream_parameters);
     let http_client = &application.http_client;
 
-    let action = future::result(request)
+    let start_cursors = match params.cursors {
+        Some(ref cursors) => Either::A(future::ok(Some(cursors.clone()))),
+        None if params.from_begin => Either::B(begin_cursors(http_client, &server_info, params.event_type).map(Some)),
+        None => Either::A(future::ok(None)),
+    };
+
+    let action = start_cursors
+        .and_then(|start_cursors| {
+            let mut request = build_request(Method::Get, &path, &server_info, None)?;
+            if let Some(cursors) = start_cursors {
+                let cursors_json = to_string(&cursors).map_err(|err| failure_detailed("Failed to JSON-serialize the cursors", err))?;
+                request.headers_mut().set(XNakadiCursors(cursors_json));
+            }
+            Ok(request)
+        })
         .and_then(move |r| execute_request(http_client, r))
         .and_then(|resp| process_response(resp, global_params, &params));
 
     match application.core.run(action) {
         Err(err) => die_failure(err),
+        Ok(_) if params.ends_cleanly => die_success(),
         Ok(_) => die_failure(failure("Stream ended abrputly!"))
     }
 }
 
-fn process_response<'a>(resp: Response, global_params: &'a GlobalParams<'a>, params: &'a Params<'a>) -> impl Future<Item=(Vec<u8>, usize), Error=Failure> + 'a {
+/// Cursors pointing at the oldest available events of all the partitions of an event type
+fn begin_cursors<'a>(http_client: &'a HttpClient, server_info: &'a ServerInfo<'a>, event_type: &str) -> impl Future<Item=Vec<Cursor>, Error=Failure> + 'a {
+    partition::fetch_partitions(http_client, server_info, event_type).map(|partitions| {
+        partitions.into_iter().map(|p| Cursor { partition: p.partition, offset: "BEGIN".to_owned() }).collect()
+    })
+}
+
+fn process_response<'a>(resp: Response, global_params: &'a GlobalParams, params: &'a Params<'a>) -> impl Future<Item=usize, Error=Failure> + 'a {
     if resp.status() != StatusCode::Ok {
-        die_failure(failure_detailed("Unexpected status code", resp.status()))
+        Either::A(unexpected_response(resp))
     } else {
-        resp.body()
-            .map(|chunk| {
-                let bytes: Vec<u8> = chunk.into_iter().collect();
-                stream::iter_ok(bytes)
-            })
-            .map_err(|err| failure_detailed("Failed to stream HTTP chunks", err))
-            .flatten()
-            .fold((Vec::new(), 0), move |(acc, i), byte| {
-
-                if byte == b'\n' {
-                    let line = String::from_utf8(acc).expect("Failed to UTF-8 decode the response");
-
-                    let batch: EventBatch = {
-                        match from_str(&line) {
-                            Err(err) => die_failure(failure_detailed("Failed to decode an event stream batch", err)),
-                            Ok(batch) => batch
+        Either::B(read_lines_utf8(resp)
+            .fold(0, move |i, line| {
+
+                let batch: EventBatch = {
+                    match from_str(&line) {
+                        Err(err) => die_failure(failure_detailed("Failed to decode an event stream batch", err)),
+                        Ok(batch) => batch
+                    }
+                };
+
+                if let Some(events) = batch.events {
+                    let event_length = events.len();
+                    if let Some(take_n) = params.take {
+                        for event in events.into_iter().take(take_n-i) {
+                            print_json_value(&Value::Object(event), global_params.clone().pretty)
                         }
-                    };
-
-                    if let Some(events) = batch.events {
-                        let event_length = events.len();
-                        if let Some(take_n) = params.take {
-                            for event in events.into_iter().take(take_n-i) {
-                                print_json_value(&Value::Object(event), global_params.clone().pretty)
-                            }
-                            if (i+event_length) >= take_n {
-                                die_success();
-                            }
-                        } else {
-                            for event in events {
-                                print_json_value(&Value::Object(event), global_params.clone().pretty)
-                            }
+                        if (i+event_length) >= take_n {
+                            die_success();
                         }
-                        future::ok((Vec::new(), i+event_length))
                     } else {
-                        future::ok((Vec::new(), i))
+                        for event in events {
+                            print_json_value(&Value::Object(event), global_params.clone().pretty)
+                        }
                     }
+                    future::ok(i+event_length)
                 } else {
-                    let mut mut_acc = acc;
-                    mut_acc.push(byte);
-                    future::ok((mut_acc, i))
+                    future::ok(i)
                 }
-            })
+            }))
     }
 }
 
-#[derive(Deserialize, Debug)]
-struct Cursor {
-    partition: String,
-    offset: String
-}
-
 #[derive(Deserialize, Debug)]
 struct EventBatch {
     cursor: Cursor,
@@ -4,6 +4,11 @@ use global::GlobalParams;
 use command_eventtype_list;
 use command_eventtype_create;
 use command_eventtype_delete;
+use command_eventtype_partitions;
+use command_eventtype_update;
+use command_eventtype_get;
+use command_eventtype_schemas;
+use command_eventtype_schema;
 
 pub const NAME: &str = "event-type";
 
@@ -12,16 +17,31 @@ pub fn sub_command<'a>() -> App<'a, 'a> {
         .subcommand(command_eventtype_create::sub_command())
         .subcommand(command_eventtype_list::sub_command())
         .subcommand(command_eventtype_delete::sub_command())
+        .subcommand(command_eventtype_partitions::sub_command())
+        .subcommand(command_eventtype_update::sub_command())
+        .subcommand(command_eventtype_get::sub_command())
+        .subcommand(command_eventtype_schemas::sub_command())
+        .subcommand(command_eventtype_schema::sub_command())
         .setting(AppSettings::SubcommandRequired)
 }
 
 pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
     if let Some(matches) = matches.subcommand_matches(command_eventtype_create::NAME) {
         command_eventtype_create::run(application, global_params, matches)
-    } else if let Some(_) = matches.subcommand_matches(command_eventtype_list::NAME) {
-        command_eventtype_list::run(application, global_params)
+    } else if let Some(matches) = matches.subcommand_matches(command_eventtype_list::NAME) {
+        command_eventtype_list::run(application, global_params, matches)
     } else if let Some(matches) = matches.subcommand_matches(command_eventtype_delete::NAME) {
         command_eventtype_delete::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_eventtype_partitions::NAME) {
+        command_eventtype_partitions::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_eventtype_update::NAME) {
+        command_eventtype_update::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_eventtype_get::NAME) {
+        command_eventtype_get::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_eventtype_schemas::NAME) {
+        command_eventtype_schemas::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_eventtype_schema::NAME) {
+        command_eventtype_schema::run(application, global_params, matches)
     } else {
         panic!("No command matched! Should have been caught by clap")
     }
@@ -16,12 +16,12 @@ const ARG_CATEGORY:                     &str = "category";
 const ARG_CATEGORY_UNDEFINED:           &str = "undefined";
 const ARG_CATEGORY_BUSINESS:            &str = "business";
 const ARG_CATEGORY_DATA:                &str = "data";
-const ARG_JSON_SCHEMA:                  &str = "json-schema";
+pub const ARG_JSON_SCHEMA:              &str = "json-schema";
 const ARG_PARTITION_STRATEGY:           &str = "partition-strategy";
 const ARG_PARTITION_STRATEGY_VALUES:    &'static [&str] = &["random", "hash"];
-const ARG_COMPATIBILITY_MODE:           &str = "compatibility-mode";
-const ARG_COMPATIBILITY_MODE_VALUES:    &[&str] = &["forward", "compatible", "none"];
-const ARG_PARTITION_KEY_FIELDS:         &str = "partition-key-fields";
+pub const ARG_COMPATIBILITY_MODE:       &str = "compatibility-mode";
+pub const ARG_COMPATIBILITY_MODE_VALUES: &[&str] = &["forward", "compatible", "none"];
+pub const ARG_PARTITION_KEY_FIELDS:     &str = "partition-key-fields";
 
 
 pub fn sub_command<'a>() -> App<'a, 'a> {
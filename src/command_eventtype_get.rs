@@ -0,0 +1,87 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use output::{die_failure, die_success, failure, failure_detailed, print_json_value};
+use server::ServerInfo;
+use http;
+use serde_json;
+use serde_json::Value;
+use json_diff::escape;
+
+pub const NAME:                         &str = "get";
+const ARG_NAME:                         &str = "name";
+const ARG_FIELD:                        &str = "field";
+const ARG_UNSTRINGIFY:                  &str = "unstringify";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Gets an event type")
+        .arg(Arg::with_name(ARG_NAME).required(true).help("The name of the event type"))
+        .arg(Arg::with_name(ARG_FIELD)
+            .long("field")
+            .takes_value(true)
+            .help("Dot-separated path of a single field of the event type to print (e.g. 'schema.schema')")
+        )
+        .arg(Arg::with_name(ARG_UNSTRINGIFY)
+            .long("unstringify")
+            .takes_value(false)
+            .requires(ARG_FIELD)
+            .help("Decodes the selected field as JSON if it is a string (e.g. the JSON Schema under 'schema.schema')")
+        )
+}
+
+struct Params<'a> {
+    name: &'a str,
+    field: Option<&'a str>,
+    unstringify: bool,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        name: matches.value_of(ARG_NAME).expect("Non-optional argument should have been caught by clap if missing"),
+        field: matches.value_of(ARG_FIELD),
+        unstringify: matches.occurrences_of(ARG_UNSTRINGIFY) > 0,
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let path = format!("/event-types/{}", params.name);
+
+    let action = http::get_json::<Value>(&application.http_client, &path, &server_info);
+
+    let definition = match application.core.run(action) {
+        Ok(definition) => definition,
+        Err(err) => die_failure(err),
+    };
+
+    let selected = match params.field {
+        Some(field) => match select_field(&definition, field) {
+            Some(value) => value,
+            None => die_failure(failure(&format!("Event type has no field {}", field))),
+        },
+        None => definition,
+    };
+
+    let selected = match selected {
+        Value::String(ref text) if params.unstringify => match serde_json::from_str(text) {
+            Ok(value) => value,
+            Err(err) => die_failure(failure_detailed("Failed to decode field as JSON", err)),
+        },
+        selected => selected,
+    };
+
+    print_json_value(&selected, global_params.pretty);
+    die_success()
+}
+
+/// Looks up a field by its dot-separated path (e.g. `schema.schema`)
+fn select_field(value: &Value, field: &str) -> Option<Value> {
+    let pointer: String = field.split('.').map(|key| format!("/{}", escape(key))).collect();
+    value.pointer(&pointer).cloned()
+}
@@ -1,28 +1,109 @@
-
-use clap::{App, SubCommand};
+use clap::{App, ArgMatches, SubCommand, Arg};
 use app::Application;
 use global::GlobalParams;
 use server::ServerInfo;
 use http;
-use hyper::{Method, StatusCode};
 use output;
+use output::{die_failure, die_success, print_json_value, OutputFormat};
+use serde_json::Value;
+use regex::Regex;
+use arg_validators;
 
-pub const NAME: &str = "list";
+pub const NAME:                         &str = "list";
+const ARG_OWNING_APPLICATION:           &str = "owning-application";
+const ARG_CATEGORY:                     &str = "category";
+const ARG_CATEGORY_VALUES:              &[&str] = &["undefined", "data", "business"];
+const ARG_NAME_REGEX:                   &str = "name-regex";
+const ARG_FORMAT:                       &str = "format";
+const ARG_FORMAT_VALUES:                &[&str] = &["json", "table"];
 
 pub fn sub_command<'a>() -> App<'a, 'a> {
-    SubCommand::with_name(NAME).about("Lists all the event types")
+    SubCommand::with_name(NAME)
+        .about("Lists all the event types")
+        .arg(Arg::with_name(ARG_OWNING_APPLICATION)
+            .long("owning-application")
+            .takes_value(true)
+            .help("Only lists the event types owned by this application")
+        )
+        .arg(Arg::with_name(ARG_CATEGORY)
+            .long("category")
+            .takes_value(true)
+            .possible_values(ARG_CATEGORY_VALUES)
+            .help("Only lists the event types of this category")
+        )
+        .arg(Arg::with_name(ARG_NAME_REGEX)
+            .long("name-regex")
+            .takes_value(true)
+            .validator(arg_validators::regex)
+            .help("Only lists the event types with names matching this regular expression")
+        )
+        .arg(Arg::with_name(ARG_FORMAT)
+            .long("format")
+            .takes_value(true)
+            .possible_values(ARG_FORMAT_VALUES)
+            .help("Prints the event types as either JSON or a summary table, which wins over --output (without it, --output table prints the summary table unless --columns is given)")
+        )
+}
+
+struct Params<'a> {
+    owning_application: Option<&'a str>,
+    category: Option<&'a str>,
+    name_regex: Option<Regex>,
+    table: Option<bool>,
 }
 
-pub fn run(application: &mut Application, global_params: &GlobalParams) {
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        owning_application: matches.value_of(ARG_OWNING_APPLICATION),
+        category: matches.value_of(ARG_CATEGORY),
+        name_regex: matches.value_of(ARG_NAME_REGEX).map(|v| Regex::new(v).expect("Invalid regex that should have been caught by clap")),
+        table: matches.value_of(ARG_FORMAT).map(|format| format == "table"),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
     let server_info = ServerInfo::from_params(global_params);
-    let action = http::execute_and_read_full_resp_body_utf8(
-        &application.http_client,
-        Method::Get,
-        "/event-types",
-        &server_info,
-        None
-    );
-    let result = application.core.run(action);
-    output::final_result(result, StatusCode::Ok, global_params.pretty)
+
+    let params = extract_params(matches);
+
+    let action = http::get_json::<Vec<Value>>(&application.http_client, "/event-types", &server_info);
+
+    let event_types: Vec<Value> = match application.core.run(action) {
+        Ok(event_types) => event_types.into_iter().filter(|event_type| is_selected(event_type, &params)).collect(),
+        Err(err) => die_failure(err),
+    };
+
+    if params.table == Some(false) {
+        // --format json wins over --output
+        output::set_output_format(OutputFormat::Json, None);
+    }
+    if global_params.summary_table(params.table) {
+        print_event_types_table(&event_types);
+    } else {
+        print_json_value(&Value::Array(event_types), global_params.pretty);
+    }
+    die_success()
 }
 
+fn is_selected(event_type: &Value, params: &Params) -> bool {
+    let field = |name: &str| event_type[name].as_str().unwrap_or("");
+    params.owning_application.map_or(true, |owning_application| field("owning_application") == owning_application) &&
+        params.category.map_or(true, |category| field("category") == category) &&
+        params.name_regex.as_ref().map_or(true, |name_regex| name_regex.is_match(field("name")))
+}
+
+fn print_event_types_table(event_types: &[Value]) {
+    let headers = ["NAME", "OWNING APPLICATION", "CATEGORY", "PARTITION STRATEGY", "COMPATIBILITY MODE", "SCHEMA VERSION"];
+    let rows: Vec<Vec<String>> = event_types.iter().map(|event_type| {
+        let cell = |value: &Value| value.as_str().unwrap_or("-").to_owned();
+        vec![
+            cell(&event_type["name"]),
+            cell(&event_type["owning_application"]),
+            cell(&event_type["category"]),
+            cell(&event_type["partition_strategy"]),
+            cell(&event_type["compatibility_mode"]),
+            cell(&event_type["schema"]["version"]),
+        ]
+    }).collect();
+    output::print_table(&headers, &rows)
+}
@@ -0,0 +1,105 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use output::{die_failure, die_success, failure_detailed};
+use server::ServerInfo;
+use http;
+use serde_json::from_str;
+use partition::Partition;
+
+pub const NAME:                         &str = "partitions";
+const ARG_NAME:                         &str = "name";
+const ARG_PARTITION:                    &str = "partition";
+const ARG_CONSUMED_OFFSET:              &str = "consumed-offset";
+const ARG_TABLE:                        &str = "table";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Gets the partitions of an event type")
+        .arg(Arg::with_name(ARG_NAME).required(true).help("The name of the event type"))
+        .arg(Arg::with_name(ARG_PARTITION)
+            .long("partition")
+            .takes_value(true)
+            .help("Only gets this partition")
+        )
+        .arg(Arg::with_name(ARG_CONSUMED_OFFSET)
+            .long("consumed-offset")
+            .takes_value(true)
+            .requires(ARG_PARTITION)
+            .help("Offset of the last consumed event, to compute the number of unconsumed events of the partition")
+        )
+        .arg(Arg::with_name(ARG_TABLE)
+            .long("table")
+            .takes_value(false)
+            .help("Prints every partition as a table row instead of JSON, which is also what --output table prints (unless --columns is given)")
+        )
+}
+
+struct Params<'a> {
+    name: &'a str,
+    partition: Option<&'a str>,
+    consumed_offset: Option<&'a str>,
+    table: Option<bool>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        name: matches.value_of(ARG_NAME).expect("Non-optional argument should have been caught by clap if missing"),
+        partition: matches.value_of(ARG_PARTITION),
+        consumed_offset: matches.value_of(ARG_CONSUMED_OFFSET),
+        table: if matches.occurrences_of(ARG_TABLE) > 0 { Some(true) } else { None },
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let path = match params.partition {
+        Some(partition) => {
+            let query_params: Vec<(&str, &str)> = params.consumed_offset.into_iter().map(|offset| ("consumed_offset", offset)).collect();
+            http::path_with_query(&format!("/event-types/{}/partitions/{}", params.name, partition), &query_params)
+        },
+        None => format!("/event-types/{}/partitions", params.name),
+    };
+
+    let action = http::execute_and_read_full_resp_body_utf8(
+        &application.http_client,
+        Method::Get,
+        &path,
+        &server_info,
+        None
+    );
+
+    match application.core.run(action) {
+        Ok((StatusCode::Ok, ref body)) if global_params.summary_table(params.table) => {
+            let decoded = if params.partition.is_some() {
+                from_str::<Partition>(body).map(|partition| vec![partition])
+            } else {
+                from_str::<Vec<Partition>>(body)
+            };
+            match decoded {
+                Ok(partitions) => print_partitions_table(&partitions),
+                Err(err) => die_failure(failure_detailed("Failed to decode partitions", err)),
+            }
+            die_success()
+        },
+        result => output::final_result(result, StatusCode::Ok, global_params.pretty),
+    }
+}
+
+fn print_partitions_table(partitions: &[Partition]) {
+    let headers = ["PARTITION", "OLDEST AVAILABLE OFFSET", "NEWEST AVAILABLE OFFSET", "UNCONSUMED EVENTS"];
+    let rows: Vec<Vec<String>> = partitions.iter().map(|partition| vec![
+        partition.partition.clone(),
+        partition.oldest_available_offset.clone(),
+        partition.newest_available_offset.clone(),
+        partition.unconsumed_events.map(|n| n.to_string()).unwrap_or("-".to_owned()),
+    ]).collect();
+    output::print_table(&headers, &rows)
+}
@@ -0,0 +1,107 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use output::{die_failure, die_success, failure_detailed, Failure};
+use server::ServerInfo;
+use http;
+use http::HttpClient;
+use futures::Future;
+use serde_json;
+use serde_json::Value;
+use json_diff;
+
+pub const NAME:                         &str = "schema";
+const ARG_NAME:                         &str = "name";
+const ARG_VERSION:                      &str = "version";
+const ARG_DIFF:                         &str = "diff";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Gets a schema version of an event type, or the differences between two of them")
+        .arg(Arg::with_name(ARG_NAME).index(1).required(true).help("The name of the event type"))
+        .arg(Arg::with_name(ARG_VERSION)
+            .index(2)
+            .required_unless(ARG_DIFF)
+            .conflicts_with(ARG_DIFF)
+            .help("The schema version (e.g. '1.0.0')")
+        )
+        .arg(Arg::with_name(ARG_DIFF)
+            .long("diff")
+            .takes_value(true)
+            .number_of_values(2)
+            .value_names(&["v1", "v2"])
+            .help("Prints the structural differences between the JSON Schemas of two versions instead")
+        )
+}
+
+struct Params<'a> {
+    name: &'a str,
+    version: Option<&'a str>,
+    diff: Option<(&'a str, &'a str)>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        name: matches.value_of(ARG_NAME).expect("Non-optional argument should have been caught by clap if missing"),
+        version: matches.value_of(ARG_VERSION),
+        diff: matches.values_of(ARG_DIFF).map(|values| {
+            let versions: Vec<&str> = values.collect();
+            (versions[0], versions[1])
+        }),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    match (params.version, params.diff) {
+        (_, Some((old_version, new_version))) => {
+            let action = fetch_json_schema(&application.http_client, &server_info, params.name, old_version)
+                .join(fetch_json_schema(&application.http_client, &server_info, params.name, new_version));
+
+            match application.core.run(action) {
+                Ok((old_schema, new_schema)) => {
+                    for difference in json_diff::diff(&old_schema, &new_schema) {
+                        println!("{}", difference);
+                    }
+                    die_success()
+                },
+                Err(err) => die_failure(err),
+            }
+        },
+        (Some(version), None) => {
+            let path = format!("/event-types/{}/schemas/{}", params.name, version);
+
+            let action = http::execute_and_read_full_resp_body_utf8(
+                &application.http_client,
+                Method::Get,
+                &path,
+                &server_info,
+                None
+            );
+
+            let result = application.core.run(action);
+            output::final_result(result, StatusCode::Ok, global_params.pretty)
+        },
+        (None, None) => panic!("Required argument should have been caught by clap if missing"),
+    }
+}
+
+/// Fetches a schema version of an event type, decoding the JSON Schema which Nakadi keeps as a string
+fn fetch_json_schema<'a>(http_client: &'a HttpClient, server_info: &'a ServerInfo<'a>, name: &str, version: &str) -> impl Future<Item=Value, Error=Failure> + 'a {
+    http::get_json::<Schema>(http_client, &format!("/event-types/{}/schemas/{}", name, version), server_info)
+        .and_then(|schema| {
+            serde_json::from_str(&schema.schema).map_err(|err| failure_detailed("Failed to decode JSON Schema", err))
+        })
+}
+
+#[derive(Deserialize, Debug)]
+struct Schema {
+    schema: String,
+}
@@ -0,0 +1,70 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use server::ServerInfo;
+use http;
+use arg_validators;
+
+pub const NAME:                         &str = "schemas";
+const ARG_NAME:                         &str = "name";
+const ARG_OFFSET:                       &str = "offset";
+const ARG_LIMIT:                        &str = "limit";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Lists the schema versions of an event type, newest first")
+        .arg(Arg::with_name(ARG_NAME).required(true).help("The name of the event type"))
+        .arg(Arg::with_name(ARG_OFFSET)
+            .long("offset")
+            .takes_value(true)
+            .validator(arg_validators::non_negative_int)
+            .help("Number of schema versions to skip")
+        )
+        .arg(Arg::with_name(ARG_LIMIT)
+            .long("limit")
+            .takes_value(true)
+            .validator(arg_validators::unsigned_int)
+            .help("Maximum number of schema versions to list")
+        )
+}
+
+struct Params<'a> {
+    name: &'a str,
+    offset: Option<&'a str>,
+    limit: Option<&'a str>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        name: matches.value_of(ARG_NAME).expect("Non-optional argument should have been caught by clap if missing"),
+        offset: matches.value_of(ARG_OFFSET),
+        limit: matches.value_of(ARG_LIMIT),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let query_params: Vec<(&str, &str)> = params.offset.map(|offset| ("offset", offset)).into_iter()
+        .chain(params.limit.map(|limit| ("limit", limit)))
+        .collect();
+
+    let path = http::path_with_query(&format!("/event-types/{}/schemas", params.name), &query_params);
+
+    let action = http::execute_and_read_full_resp_body_utf8(
+        &application.http_client,
+        Method::Get,
+        &path,
+        &server_info,
+        None
+    );
+
+    let result = application.core.run(action);
+    output::final_result(result, StatusCode::Ok, global_params.pretty)
+}
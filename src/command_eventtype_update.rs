@@ -0,0 +1,140 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg, ArgGroup};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use server::ServerInfo;
+use http;
+use serde_json;
+use serde_json::Value;
+use futures::Future;
+use input::long_argument;
+use arg_validators;
+use command_eventtype_create::{validate_json_schema, ARG_JSON_SCHEMA, ARG_COMPATIBILITY_MODE, ARG_COMPATIBILITY_MODE_VALUES, ARG_PARTITION_KEY_FIELDS};
+
+pub const NAME:                         &str = "update";
+
+const ARG_NAME:                         &str = "name";
+const ARG_RETENTION_TIME:               &str = "retention-time";
+const ARG_AUDIENCE:                     &str = "audience";
+const ARG_AUDIENCE_VALUES:              &[&str] = &["component-internal", "business-unit-internal", "company-internal", "external-partner", "external-public"];
+const ARG_DEFAULT_STATISTIC:            &str = "default-statistic";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Updates an existing event type")
+        .arg(Arg::with_name(ARG_NAME).required(true).help("The name of the event type"))
+        .arg(Arg::with_name(ARG_JSON_SCHEMA)
+            .long("json-schema")
+            .takes_value(true)
+            .validator(validate_json_schema)
+            .help("The new JSON Schema of the event type (Use '@' prefix to specify a filepath. e.g. '@schema.json')"))
+        .arg(Arg::with_name(ARG_COMPATIBILITY_MODE)
+            .long("compatibility-mode")
+            .takes_value(true)
+            .possible_values(ARG_COMPATIBILITY_MODE_VALUES)
+        )
+        .arg(Arg::with_name(ARG_PARTITION_KEY_FIELDS)
+            .multiple(true)
+            .number_of_values(1)
+            .long("partition-key-field")
+            .takes_value(true)
+        )
+        .arg(Arg::with_name(ARG_RETENTION_TIME)
+            .long("retention-time")
+            .takes_value(true)
+            .value_name("MILLISECONDS")
+            .validator(arg_validators::unsigned_int)
+            .help("The time events of the event type are retained for")
+        )
+        .arg(Arg::with_name(ARG_AUDIENCE)
+            .long("audience")
+            .takes_value(true)
+            .possible_values(ARG_AUDIENCE_VALUES)
+        )
+        .arg(Arg::with_name(ARG_DEFAULT_STATISTIC)
+            .long("default-statistic")
+            .takes_value(true)
+            .validator(arg_validators::json_object)
+            .help("JSON object of the expected statistics of the event type (e.g. '{\"messages_per_minute\": 1000, \"message_size\": 500, \"read_parallelism\": 2, \"write_parallelism\": 2}')")
+        )
+        .group(ArgGroup::with_name("changes")
+            .args(&[ARG_JSON_SCHEMA, ARG_COMPATIBILITY_MODE, ARG_PARTITION_KEY_FIELDS, ARG_RETENTION_TIME, ARG_AUDIENCE, ARG_DEFAULT_STATISTIC])
+            .multiple(true)
+            .required(true))
+}
+
+struct Params<'a> {
+    name: &'a str,
+    json_schema: Option<String>,
+    compatibility_mode: Option<&'a str>,
+    partition_key_fields: Option<Vec<&'a str>>,
+    retention_time: Option<u64>,
+    audience: Option<&'a str>,
+    default_statistic: Option<Value>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        name: matches.value_of(ARG_NAME).expect("Non-optional argument should have been caught by clap if missing"),
+        json_schema: matches.value_of(ARG_JSON_SCHEMA).and_then(|v| long_argument(v).ok()),
+        compatibility_mode: matches.value_of(ARG_COMPATIBILITY_MODE),
+        partition_key_fields: matches.values_of(ARG_PARTITION_KEY_FIELDS).map(|values| values.collect()),
+        retention_time: matches.value_of(ARG_RETENTION_TIME).map(|v| v.parse().expect("Invalid u64 that should have been caught by clap")),
+        audience: matches.value_of(ARG_AUDIENCE),
+        default_statistic: matches.value_of(ARG_DEFAULT_STATISTIC)
+            .and_then(|v| long_argument(v).ok())
+            .map(|v| serde_json::from_str(&v).expect("Failed to JSON-decode text that was validated to be JSON by clap")),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let path = format!("/event-types/{}", params.name);
+    let http_client = &application.http_client;
+    let server_info = &server_info;
+
+    let action = http::get_json::<Value>(http_client, &path, server_info)
+        .and_then(|definition| {
+            http::execute_and_read_full_resp_body_utf8(
+                http_client,
+                Method::Put,
+                &path,
+                server_info,
+                Some(&apply_changes(definition, &params))
+            )
+        });
+
+    let result = application.core.run(action);
+    output::final_result(result, StatusCode::Ok, global_params.pretty)
+}
+
+/// Applies the requested changes on top of the current definition of the event type
+fn apply_changes(definition: Value, params: &Params) -> Value {
+    let mut definition = definition;
+    if let Some(ref json_schema) = params.json_schema {
+        definition["schema"]["type"] = json!("json_schema");
+        definition["schema"]["schema"] = json!(json_schema);
+    }
+    if let Some(compatibility_mode) = params.compatibility_mode {
+        definition["compatibility_mode"] = json!(compatibility_mode);
+    }
+    if let Some(ref partition_key_fields) = params.partition_key_fields {
+        definition["partition_key_fields"] = json!(partition_key_fields);
+    }
+    if let Some(retention_time) = params.retention_time {
+        definition["options"]["retention_time"] = json!(retention_time);
+    }
+    if let Some(audience) = params.audience {
+        definition["audience"] = json!(audience);
+    }
+    if let Some(ref default_statistic) = params.default_statistic {
+        definition["default_statistic"] = default_statistic.clone();
+    }
+    definition
+}
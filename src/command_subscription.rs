@@ -0,0 +1,48 @@
+use clap::{App, SubCommand, ArgMatches, AppSettings};
+use app::Application;
+use global::GlobalParams;
+use command_subscription_create;
+use command_subscription_stream;
+use command_subscription_list;
+use command_subscription_get;
+use command_subscription_delete;
+use command_subscription_stats;
+use command_subscription_reset_cursors;
+use command_subscription_cursors;
+
+pub const NAME: &str = "subscription";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME).about("Subscriptions")
+        .subcommand(command_subscription_create::sub_command())
+        .subcommand(command_subscription_stream::sub_command())
+        .subcommand(command_subscription_list::sub_command())
+        .subcommand(command_subscription_get::sub_command())
+        .subcommand(command_subscription_delete::sub_command())
+        .subcommand(command_subscription_stats::sub_command())
+        .subcommand(command_subscription_reset_cursors::sub_command())
+        .subcommand(command_subscription_cursors::sub_command())
+        .setting(AppSettings::SubcommandRequired)
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+    if let Some(matches) = matches.subcommand_matches(command_subscription_create::NAME) {
+        command_subscription_create::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription_stream::NAME) {
+        command_subscription_stream::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription_list::NAME) {
+        command_subscription_list::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription_get::NAME) {
+        command_subscription_get::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription_delete::NAME) {
+        command_subscription_delete::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription_stats::NAME) {
+        command_subscription_stats::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription_reset_cursors::NAME) {
+        command_subscription_reset_cursors::run(application, global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription_cursors::NAME) {
+        command_subscription_cursors::run(application, global_params, matches)
+    } else {
+        panic!("No command matched! Should have been caught by clap")
+    }
+}
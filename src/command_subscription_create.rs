@@ -0,0 +1,99 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use server::ServerInfo;
+use http;
+use input::long_argument;
+use arg_validators;
+use cursor;
+use cursor::SubscriptionCursor;
+
+pub const NAME:                         &str = "create";
+
+const ARG_OWNING_APPLICATION:           &str = "owning-application";
+const ARG_EVENT_TYPES:                  &str = "event-type";
+const ARG_CONSUMER_GROUP:               &str = "consumer-group";
+const ARG_READ_FROM:                    &str = "read-from";
+const ARG_READ_FROM_VALUES:             &[&str] = &["end", "begin", "cursors"];
+const ARG_INITIAL_CURSORS:              &str = "initial-cursors";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Creates a new subscription (or gets the existing one with the same parameters)")
+        .arg(Arg::with_name(ARG_OWNING_APPLICATION).index(1).required(true).help("The owning application ID"))
+        .arg(Arg::with_name(ARG_EVENT_TYPES).index(2).required(true).multiple(true).help("Names of the event types to subscribe to"))
+        .arg(Arg::with_name(ARG_CONSUMER_GROUP)
+            .long("consumer-group")
+            .takes_value(true)
+            .required(false)
+            .default_value("default")
+            .help("The consumer group of the subscription")
+        )
+        .arg(Arg::with_name(ARG_READ_FROM)
+            .long("read-from")
+            .takes_value(true)
+            .required(false)
+            .possible_values(ARG_READ_FROM_VALUES)
+            .default_value(ARG_READ_FROM_VALUES[0])
+            .help("Position to start reading events from when the subscription is first created")
+        )
+        .arg(Arg::with_name(ARG_INITIAL_CURSORS)
+            .long("initial-cursors")
+            .takes_value(true)
+            .required_if(ARG_READ_FROM, "cursors")
+            .validator(arg_validators::subscription_cursors)
+            .help("JSON array of cursors to start reading from when --read-from=cursors (Use '@' prefix to specify a filepath. e.g. '@cursors.json')")
+        )
+}
+
+struct Params<'a> {
+    owning_application: &'a str,
+    event_types: Vec<&'a str>,
+    consumer_group: &'a str,
+    read_from: &'a str,
+    initial_cursors: Option<Vec<SubscriptionCursor>>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        owning_application: matches.value_of(ARG_OWNING_APPLICATION).expect("Non-optional argument should have been caught by clap if missing"),
+        event_types: matches.values_of(ARG_EVENT_TYPES).map(|values| values.collect()).expect("Non-optional argument should have been caught by clap if missing"),
+        consumer_group: matches.value_of(ARG_CONSUMER_GROUP).expect("Non-optional argument should have been caught by clap if missing"),
+        read_from: matches.value_of(ARG_READ_FROM).expect("Non-optional argument should have been caught by clap if missing"),
+        initial_cursors: matches.value_of(ARG_INITIAL_CURSORS)
+            .and_then(|v| long_argument(v).ok())
+            .map(|v| cursor::decode_subscription_cursors(&v).expect("Failed to decode cursors that were validated by clap")),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let mut request_body = json!({
+        "owning_application": params.owning_application,
+        "event_types": params.event_types,
+        "consumer_group": params.consumer_group,
+        "read_from": params.read_from,
+    });
+
+    if let Some(initial_cursors) = params.initial_cursors {
+        request_body["initial_cursors"] = json!(initial_cursors);
+    }
+
+    let action = http::execute_and_read_full_resp_body_utf8(
+        &application.http_client,
+        Method::Post,
+        "/subscriptions",
+        &server_info,
+        Some(&request_body)
+    );
+
+    let result = application.core.run(action);
+    output::final_result_any(result, &[StatusCode::Created, StatusCode::Ok], global_params.pretty)
+}
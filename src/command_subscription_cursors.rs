@@ -0,0 +1,74 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use output::{die_failure, die_success, print_json_value};
+use server::ServerInfo;
+use http;
+use cursor::SubscriptionCursor;
+
+pub const NAME:                         &str = "cursors";
+const ARG_SUBSCRIPTION_ID:              &str = "subscription-id";
+const ARG_RESTORABLE:                   &str = "restorable";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Gets the committed cursors of a subscription")
+        .arg(Arg::with_name(ARG_SUBSCRIPTION_ID).required(true).help("ID of the subscription"))
+        .arg(Arg::with_name(ARG_RESTORABLE)
+            .long("restorable")
+            .takes_value(false)
+            .help("Prints the cursors in the shape accepted by the reset-cursors command")
+        )
+}
+
+struct Params<'a> {
+    subscription_id: &'a str,
+    restorable: bool,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        subscription_id: matches.value_of(ARG_SUBSCRIPTION_ID).expect("Non-optional argument should have been caught by clap if missing"),
+        restorable: matches.occurrences_of(ARG_RESTORABLE) > 0,
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let path = format!("/subscriptions/{}/cursors", params.subscription_id);
+
+    if params.restorable {
+        let action = http::get_json::<Cursors>(&application.http_client, &path, &server_info);
+        match application.core.run(action) {
+            Ok(cursors) => {
+                let items: Vec<SubscriptionCursor> = cursors.items.into_iter().map(SubscriptionCursor::without_token).collect();
+                print_json_value(&json!({ "items": items }), global_params.pretty);
+                die_success()
+            },
+            Err(err) => die_failure(err),
+        }
+    } else {
+        let action = http::execute_and_read_full_resp_body_utf8(
+            &application.http_client,
+            Method::Get,
+            &path,
+            &server_info,
+            None
+        );
+
+        let result = application.core.run(action);
+        output::final_result(result, StatusCode::Ok, global_params.pretty)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Cursors {
+    items: Vec<SubscriptionCursor>,
+}
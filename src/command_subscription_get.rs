@@ -0,0 +1,47 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use server::ServerInfo;
+use http;
+
+pub const NAME:                         &str = "get";
+const ARG_SUBSCRIPTION_ID:              &str = "subscription-id";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Gets a subscription")
+        .arg(Arg::with_name(ARG_SUBSCRIPTION_ID).required(true).help("ID of the subscription"))
+}
+
+struct Params<'a> {
+    subscription_id: &'a str,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        subscription_id: matches.value_of(ARG_SUBSCRIPTION_ID).expect("Non-optional argument should have been caught by clap if missing"),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let path = format!("/subscriptions/{}", params.subscription_id);
+
+    let action = http::execute_and_read_full_resp_body_utf8(
+        &application.http_client,
+        Method::Get,
+        &path,
+        &server_info,
+        None
+    );
+
+    let result = application.core.run(action);
+    output::final_result(result, StatusCode::Ok, global_params.pretty)
+}
@@ -0,0 +1,101 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use server::ServerInfo;
+use http;
+use futures::future;
+use futures::future::Loop;
+use futures::Future;
+use serde_json::Value;
+use output::{die_failure, die_success, print_json_value};
+
+pub const NAME:                         &str = "list";
+const ARG_OWNING_APPLICATION:           &str = "owning-application";
+const ARG_EVENT_TYPES:                  &str = "event-type";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Lists all the subscriptions (following all the result pages)")
+        .arg(Arg::with_name(ARG_OWNING_APPLICATION)
+            .long("owning-application")
+            .takes_value(true)
+            .required(false)
+            .help("Only lists subscriptions of this owning application")
+        )
+        .arg(Arg::with_name(ARG_EVENT_TYPES)
+            .long("event-type")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .help("Only lists subscriptions to this event type (can be specified multiple times)")
+        )
+}
+
+struct Params<'a> {
+    owning_application: Option<&'a str>,
+    event_types: Vec<&'a str>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        owning_application: matches.value_of(ARG_OWNING_APPLICATION),
+        event_types: matches.values_of(ARG_EVENT_TYPES).map(|values| values.collect()).unwrap_or(vec![]),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let mut query_params = Vec::new();
+    if let Some(owning_application) = params.owning_application {
+        query_params.push(("owning_application", owning_application));
+    }
+    for event_type in params.event_types {
+        query_params.push(("event_type", event_type));
+    }
+
+    let first_page_path = http::path_with_query("/subscriptions", &query_params);
+    let http_client = &application.http_client;
+    let server_info = &server_info;
+
+    let action = future::loop_fn((first_page_path, Vec::new()), move |(path, mut subscriptions)| {
+        http::get_json::<SubscriptionsPage>(http_client, &path, server_info).map(move |page| {
+            let page_length = page.items.len();
+            subscriptions.extend(page.items);
+            match page.links.next {
+                Some(ref next) if page_length > 0 => Loop::Continue((next.href.clone(), subscriptions)),
+                _ => Loop::Break(subscriptions),
+            }
+        })
+    });
+
+    match application.core.run(action) {
+        Ok(subscriptions) => {
+            print_json_value(&Value::Array(subscriptions), global_params.pretty);
+            die_success()
+        },
+        Err(err) => die_failure(err),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct SubscriptionsPage {
+    items: Vec<Value>,
+    #[serde(rename = "_links")]
+    links: PaginationLinks,
+}
+
+#[derive(Deserialize, Debug)]
+struct PaginationLinks {
+    next: Option<PaginationLink>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PaginationLink {
+    href: String,
+}
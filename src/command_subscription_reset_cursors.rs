@@ -0,0 +1,125 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg, ArgGroup};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use server::ServerInfo;
+use http;
+use http::HttpClient;
+use futures::future;
+use futures::future::Either;
+use futures::Future;
+use input::long_argument;
+use arg_validators;
+use partition;
+use output::Failure;
+use cursor;
+use cursor::SubscriptionCursor;
+
+pub const NAME:                         &str = "reset-cursors";
+const ARG_SUBSCRIPTION_ID:              &str = "subscription-id";
+const ARG_CURSORS:                      &str = "cursors";
+const ARG_TO:                           &str = "to";
+const ARG_TO_VALUES:                    &[&str] = &["begin", "end"];
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Resets the committed cursors of a subscription")
+        .arg(Arg::with_name(ARG_SUBSCRIPTION_ID).required(true).help("ID of the subscription"))
+        .arg(Arg::with_name(ARG_CURSORS)
+            .long("cursors")
+            .takes_value(true)
+            .validator(arg_validators::subscription_cursors)
+            .help("JSON array of the cursors to reset to, or an object with the array under \"items\" (Use '@' prefix to specify a filepath. e.g. '@cursors.json')")
+        )
+        .arg(Arg::with_name(ARG_TO)
+            .long("to")
+            .takes_value(true)
+            .possible_values(ARG_TO_VALUES)
+            .help("Resets the cursors of all the partitions to either the beginning or the end of the event types")
+        )
+        .group(ArgGroup::with_name("position").args(&[ARG_CURSORS, ARG_TO]).required(true))
+}
+
+enum Position {
+    Begin,
+    End,
+}
+
+struct Params<'a> {
+    subscription_id: &'a str,
+    cursors: Option<Vec<SubscriptionCursor>>,
+    to: Option<Position>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        subscription_id: matches.value_of(ARG_SUBSCRIPTION_ID).expect("Non-optional argument should have been caught by clap if missing"),
+        cursors: matches.value_of(ARG_CURSORS)
+            .and_then(|v| long_argument(v).ok())
+            .map(|v| cursor::decode_subscription_cursors(&v).expect("Failed to decode cursors that were validated by clap")),
+        to: matches.value_of(ARG_TO).map(|v| if v == "begin" { Position::Begin } else { Position::End }),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let path = format!("/subscriptions/{}/cursors", params.subscription_id);
+    let http_client = &application.http_client;
+    let server_info = &server_info;
+
+    let cursors = match (params.cursors, params.to) {
+        (Some(cursors), _) => Either::A(future::ok(cursors)),
+        (None, Some(position)) => Either::B(cursors_at(http_client, server_info, params.subscription_id, position)),
+        (None, None) => panic!("Required argument should have been caught by clap if missing"),
+    };
+
+    let action = cursors.and_then(|cursors| {
+        http::execute_and_read_full_resp_body_utf8(
+            http_client,
+            Method::Patch,
+            &path,
+            server_info,
+            Some(&json!({ "items": cursors }))
+        )
+    });
+
+    let result = application.core.run(action);
+    output::final_result(result, StatusCode::NoContent, global_params.pretty)
+}
+
+/// Computes the cursors pointing at either the beginning or the end of every partition of the event types of a subscription
+fn cursors_at<'a>(http_client: &'a HttpClient, server_info: &'a ServerInfo<'a>, subscription_id: &str, position: Position) -> impl Future<Item=Vec<SubscriptionCursor>, Error=Failure> + 'a {
+    http::get_json::<Subscription>(http_client, &format!("/subscriptions/{}", subscription_id), server_info)
+        .and_then(move |subscription| {
+            future::join_all(subscription.event_types.into_iter().map(move |event_type| {
+                partition::fetch_partitions(http_client, server_info, &event_type).map(move |partitions| (event_type, partitions))
+            }))
+        })
+        .map(move |event_type_partitions| {
+            event_type_partitions.into_iter().flat_map(|(event_type, partitions)| {
+                let offsets: Vec<(String, String)> = partitions.into_iter().map(|p| {
+                    match position {
+                        Position::Begin => (p.partition, "begin".to_owned()),
+                        Position::End => (p.partition, p.newest_available_offset),
+                    }
+                }).collect();
+                offsets.into_iter().map(move |(partition, offset)| SubscriptionCursor {
+                    event_type: event_type.clone(),
+                    partition,
+                    offset,
+                    cursor_token: None,
+                })
+            }).collect()
+        })
+}
+
+#[derive(Deserialize, Debug)]
+struct Subscription {
+    event_types: Vec<String>,
+}
@@ -0,0 +1,136 @@
+
+use clap::{App, ArgMatches, SubCommand, Arg};
+use app::Application;
+use global::GlobalParams;
+use hyper::{Method, StatusCode};
+use output;
+use output::{die_failure, die_success, failure, failure_detailed};
+use server::ServerInfo;
+use http;
+use serde_json::from_str;
+use arg_validators;
+
+pub const NAME:                         &str = "stats";
+const ARG_SUBSCRIPTION_ID:              &str = "subscription-id";
+const ARG_TABLE:                        &str = "table";
+const ARG_MAX_LAG:                      &str = "max-lag";
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Gets the consumption statistics of a subscription")
+        .arg(Arg::with_name(ARG_SUBSCRIPTION_ID).required(true).help("ID of the subscription"))
+        .arg(Arg::with_name(ARG_TABLE)
+            .long("table")
+            .takes_value(false)
+            .help("Prints the statistics of every partition as a table row instead of JSON, which is also what --output table prints (unless --columns is given)")
+        )
+        .arg(Arg::with_name(ARG_MAX_LAG)
+            .long("max-lag")
+            .takes_value(true)
+            .value_name("SECONDS")
+            .validator(arg_validators::unsigned_int)
+            .help("Exits with failure if the consumer lag of any partition exceeds this many seconds")
+        )
+}
+
+struct Params<'a> {
+    subscription_id: &'a str,
+    table: Option<bool>,
+    max_lag: Option<u64>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        subscription_id: matches.value_of(ARG_SUBSCRIPTION_ID).expect("Non-optional argument should have been caught by clap if missing"),
+        table: if matches.occurrences_of(ARG_TABLE) > 0 { Some(true) } else { None },
+        max_lag: matches.value_of(ARG_MAX_LAG).map(|v| v.parse().expect("Invalid u64 that should have been caught by clap")),
+    }
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+
+    let server_info = ServerInfo::from_params(global_params);
+
+    let params = extract_params(matches);
+
+    let path = http::path_with_query(&format!("/subscriptions/{}/stats", params.subscription_id), &[("show_time_lag", "true")]);
+
+    let action = http::execute_and_read_full_resp_body_utf8(
+        &application.http_client,
+        Method::Get,
+        &path,
+        &server_info,
+        None
+    );
+
+    match application.core.run(action) {
+        Ok((StatusCode::Ok, body)) => {
+            let stats: SubscriptionStats = match from_str(&body) {
+                Ok(stats) => stats,
+                Err(err) => die_failure(failure_detailed("Failed to decode subscription stats", err)),
+            };
+
+            if global_params.summary_table(params.table) {
+                print_stats_table(&stats);
+            } else {
+                output::print_json(&body, global_params.pretty);
+            }
+
+            if let Some(max_lag) = params.max_lag {
+                check_lag(&stats, max_lag);
+            }
+            die_success()
+        },
+        result => output::final_result(result, StatusCode::Ok, global_params.pretty),
+    }
+}
+
+fn print_stats_table(stats: &SubscriptionStats) {
+    let headers = ["EVENT TYPE", "PARTITION", "STATE", "STREAM ID", "UNCONSUMED EVENTS", "LAG (SECONDS)"];
+    let rows: Vec<Vec<String>> = stats.items.iter().flat_map(|event_type_stats| {
+        event_type_stats.partitions.iter().map(move |partition| vec![
+            event_type_stats.event_type.clone(),
+            partition.partition.clone(),
+            partition.state.clone(),
+            partition.stream_id.clone().unwrap_or("-".to_owned()),
+            partition.unconsumed_events.map(|n| n.to_string()).unwrap_or("-".to_owned()),
+            partition.consumer_lag_seconds.map(|n| n.to_string()).unwrap_or("-".to_owned()),
+        ])
+    }).collect();
+    output::print_table(&headers, &rows)
+}
+
+/// Exits with failure if any partition is lagging behind by more than `max_lag` seconds
+fn check_lag(stats: &SubscriptionStats, max_lag: u64) {
+    for event_type_stats in &stats.items {
+        for partition in &event_type_stats.partitions {
+            if let Some(lag) = partition.consumer_lag_seconds {
+                if lag > max_lag {
+                    die_failure(failure(&format!(
+                        "Consumer lag of {} seconds on partition {} of {} exceeds {} seconds",
+                        lag, partition.partition, event_type_stats.event_type, max_lag)))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct SubscriptionStats {
+    items: Vec<EventTypeStats>,
+}
+
+#[derive(Deserialize, Debug)]
+struct EventTypeStats {
+    event_type: String,
+    partitions: Vec<PartitionStats>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PartitionStats {
+    partition: String,
+    state: String,
+    unconsumed_events: Option<u64>,
+    consumer_lag_seconds: Option<u64>,
+    stream_id: Option<String>,
+}
@@ -0,0 +1,146 @@
+use http::*;
+use futures::future;
+use futures::future::Either;
+use server::ServerInfo;
+use futures::Future;
+use app::Application;
+use hyper::{Method, Response, StatusCode};
+use output::{die_failure, failure_detailed, failure_response, print_json_value, require_streamable_output_format, Failure, die_success, failure};
+use futures::Stream;
+use serde_json::{Value, Map, from_str};
+use global::*;
+use clap::{ArgMatches, App, SubCommand, Arg};
+use arg_validators;
+use cursor::SubscriptionCursor;
+
+pub const NAME: &str = "stream";
+const ARG_SUBSCRIPTION_ID: &str = "subscription-id";
+const ARG_TAKE: &str = "take";
+
+struct Params<'a> {
+    subscription_id: &'a str,
+    take: Option<usize>,
+}
+
+fn extract_params<'a>(matches: &'a ArgMatches) -> Params<'a> {
+    Params {
+        subscription_id: matches.value_of(ARG_SUBSCRIPTION_ID).expect("Non-optional argument should have been caught by clap if missing"),
+        take: matches.value_of(ARG_TAKE).and_then(|v| v.parse().ok()),
+    }
+}
+
+pub fn sub_command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(NAME)
+        .about("Stream-listen on events from a subscription, committing the cursor of each consumed batch")
+        .arg(Arg::with_name(ARG_SUBSCRIPTION_ID).required(true).index(1).help("ID of the subscription"))
+        .arg(Arg::with_name(ARG_TAKE).long("take").short("n").takes_value(true).value_name("N").help("Exits after consuming N events from the stream. The cursor of a batch consumed only partially is not committed, so its events (including the printed ones) are delivered again by the next stream").validator(arg_validators::unsigned_int))
+}
+
+pub fn run(application: &mut Application, global_params: &GlobalParams, matches: &ArgMatches) {
+    require_streamable_output_format();
+    let params = extract_params(matches);
+    let server_info = ServerInfo::from_params(global_params);
+
+    let path = format!("/subscriptions/{}/events", params.subscription_id);
+    let request = build_request(Method::Get, &path, &server_info, None);
+    let http_client = &application.http_client;
+
+    let action = future::result(request)
+        .and_then(move |r| execute_request(http_client, r))
+        .and_then(|resp| process_response(resp, http_client, &server_info, global_params, &params));
+
+    match application.core.run(action) {
+        Err(err) => die_failure(err),
+        Ok(_) => die_failure(failure("Stream ended abruptly!"))
+    }
+}
+
+fn process_response<'a>(
+    resp: Response,
+    http_client: &'a HttpClient,
+    server_info: &'a ServerInfo<'a>,
+    global_params: &'a GlobalParams,
+    params: &'a Params<'a>) -> impl Future<Item=usize, Error=Failure> + 'a {
+
+    if resp.status() != StatusCode::Ok {
+        return Either::A(unexpected_response(resp))
+    }
+
+    let stream_id = match resp.headers().get::<XNakadiStreamId>() {
+        Some(header) => header.0.clone(),
+        None => die_failure(failure("Missing X-Nakadi-StreamId header in the stream response")),
+    };
+
+    Either::B(read_lines_utf8(resp)
+        .fold(0, move |i, line| {
+
+            let batch: EventBatch = {
+                match from_str(&line) {
+                    Err(err) => die_failure(failure_detailed("Failed to decode an event stream batch", err)),
+                    Ok(batch) => batch
+                }
+            };
+
+            if let Some(events) = batch.events {
+                let event_length = events.len();
+                let take_length = params.take.map(|take_n| take_n - i).unwrap_or(event_length);
+
+                for event in events.into_iter().take(take_length) {
+                    print_json_value(&Value::Object(event), global_params.pretty)
+                }
+
+                if take_length < event_length {
+                    // Only part of the batch got consumed, so its cursor is left uncommitted
+                    die_success()
+                }
+
+                let consumed = i + event_length;
+                let done = params.take.map(|take_n| consumed >= take_n).unwrap_or(false);
+                let commit = commit_cursor(http_client, server_info, params.subscription_id, &stream_id, batch.cursor)
+                    .map(move |_| {
+                        if done {
+                            die_success()
+                        }
+                        consumed
+                    });
+                Either::A(commit)
+            } else {
+                Either::B(future::ok(i))
+            }
+        }))
+}
+
+/// Commits the cursor of a consumed batch within the stream identified by `stream_id`
+fn commit_cursor<'a>(
+    http_client: &'a HttpClient,
+    server_info: &'a ServerInfo<'a>,
+    subscription_id: &str,
+    stream_id: &str,
+    cursor: SubscriptionCursor) -> impl Future<Item=(), Error=Failure> + 'a {
+
+    let path = format!("/subscriptions/{}/cursors", subscription_id);
+    let body = json!({ "items": [cursor] });
+    let stream_id = stream_id.to_owned();
+
+    future::result(build_request(Method::Post, &path, server_info, Some(&body)))
+        .and_then(move |mut request| {
+            request.headers_mut().set(XNakadiStreamId(stream_id));
+            execute_request(http_client, request)
+        })
+        .and_then(|resp| {
+            let status = resp.status();
+            read_full_resp_body_utf8(resp).and_then(move |body| {
+                if status == StatusCode::NoContent || status == StatusCode::Ok {
+                    Ok(())
+                } else {
+                    Err(failure_response(&format!("Failed to commit cursor ({})", status), status, body))
+                }
+            })
+        })
+}
+
+#[derive(Deserialize, Debug)]
+struct EventBatch {
+    cursor: SubscriptionCursor,
+    events: Option<Vec<Map<String, Value>>>
+}
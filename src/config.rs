@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use toml;
+use dirs;
+use output::{Failure, failure, failure_detailed};
+use auth::private_file;
+
+/// Name of the profile used when none is selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Contents of the configuration file
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Named settings of a Nakadi server
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthMethod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bearer_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_cache_ttl: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oauth2_token_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oauth2_client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oauth2_client_secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oauth2_scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pretty: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthMethod {
+    None,
+    BearerToken,
+    Zign,
+    TokenCommand,
+    #[serde(rename = "oauth2-client-credentials")]
+    OAuth2ClientCredentials,
+}
+
+/// Keys of a profile that can be set, as named in the configuration file
+pub const PROFILE_KEYS: &[&str] = &[
+    "url", "auth", "bearer_token", "token_command", "token_cache_ttl", "oauth2_token_url", "oauth2_client_id", "oauth2_client_secret", "oauth2_scope", "network_timeout", "pretty"
+];
+
+/// Shown by `config show` in place of secrets, unless they're revealed
+const MASKED_SECRET: &str = "********";
+
+/// Values of the `auth` key of a profile
+pub const AUTH_METHODS: &[&str] = &["none", "bearer-token", "zign", "token-command", "oauth2-client-credentials"];
+
+impl Profile {
+
+    /// Masks the bearer token and the OAuth2 client secret, except for references to the files holding them (`@FILEPATH`)
+    pub fn mask_secrets(&mut self) {
+        for secret in [&mut self.bearer_token, &mut self.oauth2_client_secret].iter_mut() {
+            if secret.as_ref().map(|value| !value.starts_with('@')).unwrap_or(false) {
+                **secret = Some(MASKED_SECRET.to_owned());
+            }
+        }
+    }
+
+    /// Sets one of the `PROFILE_KEYS` to a value given as text
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Failure> {
+        match key {
+            "url" => self.url = Some(value.to_owned()),
+            "auth" => self.auth = Some(match value {
+                "none" => AuthMethod::None,
+                "bearer-token" => AuthMethod::BearerToken,
+                "zign" => AuthMethod::Zign,
+                "token-command" => AuthMethod::TokenCommand,
+                "oauth2-client-credentials" => AuthMethod::OAuth2ClientCredentials,
+                _ => return Err(failure(&format!("auth should be one of: {}", AUTH_METHODS.join(", ")))),
+            }),
+            "bearer_token" => self.bearer_token = Some(value.to_owned()),
+            "token_command" => self.token_command = Some(value.to_owned()),
+            "token_cache_ttl" => self.token_cache_ttl = Some(value.parse().map_err(|_| failure("token_cache_ttl should be a positive integer"))?),
+            "oauth2_token_url" => self.oauth2_token_url = Some(value.to_owned()),
+            "oauth2_client_id" => self.oauth2_client_id = Some(value.to_owned()),
+            "oauth2_client_secret" => self.oauth2_client_secret = Some(value.to_owned()),
+            "oauth2_scope" => self.oauth2_scope = Some(value.to_owned()),
+            "network_timeout" => self.network_timeout = Some(value.parse().map_err(|_| failure("network_timeout should be a positive integer"))?),
+            "pretty" => self.pretty = Some(value.parse().map_err(|_| failure("pretty should be either true or false"))?),
+            _ => return Err(failure(&format!("Unknown profile key {}", key))),
+        }
+        Ok(())
+    }
+}
+
+/// Path of the configuration file: `$XDG_CONFIG_HOME/nakacli/config.toml`, defaulting to `~/.config/nakacli/config.toml`
+pub fn path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+    config_home.map(|config_home| config_home.join("nakacli").join("config.toml"))
+}
+
+/// Loads the configuration file, which is considered empty if it doesn't exist
+pub fn load() -> Result<Config, Failure> {
+    let path = match path() {
+        Some(path) => path,
+        None => return Ok(Config::default()),
+    };
+    let mut content = String::new();
+    match File::open(&path) {
+        Ok(mut file) => file.read_to_string(&mut content).map_err(|err| failure_detailed(&format!("Could not read from the file {}", path.display()), err))?,
+        Err(ref err) if err.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(err) => return Err(failure_detailed(&format!("Could not open file {}", path.display()), err)),
+    };
+    toml::from_str(&content).map_err(|err| failure_detailed(&format!("Invalid configuration file {}", path.display()), err))
+}
+
+/// Writes the configuration file, creating its directory if missing
+pub fn save(config: &Config) -> Result<(), Failure> {
+    let path = path().ok_or(failure("Could not find the home directory to write the configuration file to"))?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|err| failure_detailed(&format!("Could not create directory {}", directory.display()), err))?;
+    }
+    let content = toml::to_string(config).map_err(|err| failure_detailed("Failed to encode configuration", err))?;
+    let mut file = private_file(&path).map_err(|err| failure_detailed(&format!("Could not create file {}", path.display()), err))?;
+    file.write_all(content.as_bytes()).map_err(|err| failure_detailed(&format!("Could not write to the file {}", path.display()), err))
+}
@@ -0,0 +1,46 @@
+use serde_json;
+
+/// Position within a partition of an event type
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cursor {
+    pub partition: String,
+    pub offset: String,
+}
+
+/// Position within a partition of one of the event types of a subscription
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubscriptionCursor {
+    pub event_type: String,
+    pub partition: String,
+    pub offset: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_token: Option<String>,
+}
+
+impl SubscriptionCursor {
+    /// The same cursor without its `cursor_token`, which is the shape Nakadi accepts when setting cursors
+    pub fn without_token(self) -> SubscriptionCursor {
+        SubscriptionCursor { cursor_token: None, ..self }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum SubscriptionCursors {
+    Items { items: Vec<SubscriptionCursor> },
+    Array(Vec<SubscriptionCursor>),
+}
+
+/// Decodes a JSON array of cursors
+pub fn decode_cursors(json: &str) -> Result<Vec<Cursor>, String> {
+    serde_json::from_str(json).map_err(|err| format!("Cursors should be a JSON array of objects with partition and offset fields: {}", err))
+}
+
+/// Decodes subscription cursors from either a JSON array of cursors, or an object with the array under `items`
+pub fn decode_subscription_cursors(json: &str) -> Result<Vec<SubscriptionCursor>, String> {
+    match serde_json::from_str(json) {
+        Ok(SubscriptionCursors::Items { items }) => Ok(items),
+        Ok(SubscriptionCursors::Array(cursors)) => Ok(cursors),
+        Err(err) => Err(format!("Cursors should be a JSON array of objects with event_type, partition and offset fields: {}", err)),
+    }
+}
@@ -2,28 +2,151 @@
 use clap::{Arg, ArgMatches};
 use std::time::Duration;
 use arg_validators;
+use config;
+use config::{AuthMethod, Profile};
+use output::{Failure, failure, ErrorFormat, OutputFormat};
+use auth::{OAuth2ClientCredentials, TokenCommand, ZIGN_COMMAND};
+use input::long_argument;
+use uuid::Uuid;
 
 const ARG_PRETTY: &str = "pretty";
 const ARG_ZIGN: &str = "zign";
+const ARG_TOKEN_COMMAND: &str = "token-command";
+const ARG_TOKEN_CACHE_TTL: &str = "token-cache-ttl";
 const ARG_BEARER_TOKEN: &str = "bearer_token";
 const ARG_NAKADI_URL: &str = "nakadi_url";
 const ARG_NETWORK_TIMEOUT: &str = "network-timeout";
+const ARG_PROFILE: &str = "profile";
+const ARG_FLOW_ID: &str = "flow-id";
+const ARG_VERBOSE: &str = "verbose";
+const ARG_OUTPUT: &str = "output";
+const ARG_OUTPUT_VALUES: &[&str] = &["json", "ndjson", "yaml", "table"];
+const ARG_COLUMNS: &str = "columns";
+const ARG_ERROR_FORMAT: &str = "error-format";
+const ARG_ERROR_FORMAT_VALUES: &[&str] = &["text", "json"];
+const ARG_OAUTH2_TOKEN_URL: &str = "oauth2-token-url";
+const ARG_OAUTH2_CLIENT_ID: &str = "oauth2-client-id";
+const ARG_OAUTH2_CLIENT_SECRET: &str = "oauth2-client-secret";
+const ARG_OAUTH2_SCOPE: &str = "oauth2-scope";
 
-pub struct GlobalParams<'a> {
+const DEFAULT_NETWORK_TIMEOUT_SECONDS: u64 = 1;
+
+pub struct GlobalParams {
     pub pretty: bool,
-    pub zign: bool,
-    pub bearer_token: Option<&'a str>,
-    pub nakadi_url: Option<&'a str>,
+    pub token_command: Option<TokenCommand>,
+    pub bearer_token: Option<String>,
+    pub oauth2: Option<OAuth2ClientCredentials>,
+    pub nakadi_url: Option<String>,
     pub network_timeout: Option<Duration>,
+    pub flow_id: String,
+    pub verbosity: u64,
+    pub output_format: OutputFormat,
+    pub columns: Option<Vec<String>>,
+}
+
+impl GlobalParams {
+
+    /// Whether a command prints its own summary table, as selected by its own flag (`Some`) which wins over `--output`,
+    /// otherwise by `--output table`. Selecting `--columns` prints those fields of the JSON as a table instead.
+    pub fn summary_table(&self, selected: Option<bool>) -> bool {
+        selected.unwrap_or(self.output_format == OutputFormat::Table) && self.columns.is_none()
+    }
+}
+
+/// Resolves every global parameter from either its flag, its environment variable, the selected profile
+/// of the configuration file, or its default, in that order
+pub fn extract_global_params(matches: &ArgMatches) -> Result<GlobalParams, Failure> {
+    let profile = selected_profile(matches)?;
+
+    let token_cache_ttl = matches.value_of(ARG_TOKEN_CACHE_TTL)
+        .map(|v| v.parse::<u64>().expect("Invalid u64 that should have been caught by clap"))
+        .or(profile.token_cache_ttl);
+    let token_command = |command: &str| TokenCommand { command: command.to_owned(), cache_ttl: token_cache_ttl };
+
+    let (token_command, bearer_token, oauth2) =
+        if matches.occurrences_of(ARG_ZIGN) > 0 {
+            (Some(token_command(ZIGN_COMMAND)), None, None)
+        } else if let Some(command) = matches.value_of(ARG_TOKEN_COMMAND) {
+            (Some(token_command(command)), None, None)
+        } else if let Some(bearer_token) = matches.value_of(ARG_BEARER_TOKEN) {
+            (None, Some(bearer_token.to_owned()), None)
+        } else if matches.is_present(ARG_OAUTH2_TOKEN_URL) {
+            (None, None, Some(oauth2_client_credentials(matches, &profile)?))
+        } else {
+            match profile.auth {
+                Some(AuthMethod::Zign) => (Some(token_command(ZIGN_COMMAND)), None, None),
+                Some(AuthMethod::TokenCommand) => {
+                    let command = profile.token_command.as_ref().ok_or(failure("token_command is required by the token-command auth of the profile"))?;
+                    (Some(token_command(command)), None, None)
+                },
+                Some(AuthMethod::None) => (None, None, None),
+                Some(AuthMethod::OAuth2ClientCredentials) => (None, None, Some(oauth2_client_credentials(matches, &profile)?)),
+                Some(AuthMethod::BearerToken) | None => (None, profile.bearer_token.clone(), None),
+            }
+        };
+
+    let network_timeout_seconds = matches.value_of(ARG_NETWORK_TIMEOUT)
+        .map(|v| v.parse::<u64>().expect("Invalid u64 that should have been caught by clap"))
+        .or(profile.network_timeout)
+        .unwrap_or(DEFAULT_NETWORK_TIMEOUT_SECONDS);
+
+    let output_format = match matches.value_of(ARG_OUTPUT) {
+        Some("ndjson") => OutputFormat::Ndjson,
+        Some("yaml") => OutputFormat::Yaml,
+        Some("table") => OutputFormat::Table,
+        _ => OutputFormat::Json,
+    };
+    if matches.is_present(ARG_COLUMNS) && output_format != OutputFormat::Table {
+        return Err(failure("--columns is only supported with --output table"))
+    }
+
+    Ok(GlobalParams {
+        pretty: matches.occurrences_of(ARG_PRETTY) > 0 || profile.pretty.unwrap_or(false),
+        token_command,
+        bearer_token,
+        oauth2,
+        nakadi_url: matches.value_of(ARG_NAKADI_URL).map(str::to_owned).or(profile.url),
+        network_timeout: Some(Duration::from_secs(network_timeout_seconds)),
+        verbosity: matches.occurrences_of(ARG_VERBOSE),
+        output_format,
+        columns: matches.values_of(ARG_COLUMNS).map(|values| values.map(str::to_owned).collect()),
+        flow_id: matches.value_of(ARG_FLOW_ID).map(str::to_owned).unwrap_or_else(|| Uuid::new_v4().to_string()),
+    })
+}
+
+/// The OAuth2 client credentials, where the client ID and secret can also be read from files using the `@FILEPATH` syntax
+fn oauth2_client_credentials(matches: &ArgMatches, profile: &Profile) -> Result<OAuth2ClientCredentials, Failure> {
+    let setting = |arg: &str, profile_value: &Option<String>| -> Option<String> {
+        matches.value_of(arg).map(str::to_owned).or_else(|| profile_value.clone())
+    };
+    let secret = |arg: &str, profile_value: &Option<String>| -> Result<String, Failure> {
+        let value = setting(arg, profile_value).ok_or(failure(&format!("--{} is required for OAuth2 client credentials", arg)))?;
+        long_argument(&value).map(|content| content.trim().to_owned()).map_err(|err| failure(&err))
+    };
+
+    Ok(OAuth2ClientCredentials {
+        token_url: setting(ARG_OAUTH2_TOKEN_URL, &profile.oauth2_token_url)
+            .ok_or(failure(&format!("--{} is required for OAuth2 client credentials", ARG_OAUTH2_TOKEN_URL)))?,
+        client_id: secret(ARG_OAUTH2_CLIENT_ID, &profile.oauth2_client_id)?,
+        client_secret: secret(ARG_OAUTH2_CLIENT_SECRET, &profile.oauth2_client_secret)?,
+        scope: setting(ARG_OAUTH2_SCOPE, &profile.oauth2_scope),
+    })
 }
 
-pub fn extract_global_params<'a>(matches: &'a ArgMatches) -> GlobalParams<'a> {
-    GlobalParams {
-        pretty: matches.occurrences_of(ARG_PRETTY) > 0,
-        zign: matches.occurrences_of(ARG_ZIGN) > 0,
-        bearer_token: matches.value_of(ARG_BEARER_TOKEN),
-        nakadi_url: matches.value_of(ARG_NAKADI_URL),
-        network_timeout: matches.value_of(ARG_NETWORK_TIMEOUT).map(|v| Duration::from_secs(v.parse::<u64>().expect("Invalid u64 that should have been caught by clap"))),
+/// The format of failures, which is resolved apart from the other global parameters since extracting them can fail too
+pub fn error_format(matches: &ArgMatches) -> ErrorFormat {
+    match matches.value_of(ARG_ERROR_FORMAT) {
+        Some("json") => ErrorFormat::Json,
+        _ => ErrorFormat::Text,
+    }
+}
+
+/// The profile selected using `--profile`, otherwise the default profile if configured
+fn selected_profile(matches: &ArgMatches) -> Result<Profile, Failure> {
+    let mut config = config::load()?;
+    match matches.value_of(ARG_PROFILE) {
+        Some(name) => config.profiles.remove(name).ok_or(failure(&format!("No profile named {}", name))),
+        None => Ok(config.profiles.remove(config::DEFAULT_PROFILE).unwrap_or_default()),
     }
 }
 
@@ -45,11 +168,55 @@ pub fn global_args() -> Vec<Arg<'static, 'static>> {
 
     let zign = Arg::with_name(ARG_ZIGN)
         .long("zign")
-        .help("Use zign to acquire a Bearer token")
+        .help("Use zign to acquire a Bearer token (same as --token-command 'zign token')")
         .takes_value(false)
         .global(true)
+        .conflicts_with_all(&[ARG_BEARER_TOKEN, ARG_TOKEN_COMMAND]);
+
+    let token_command = Arg::with_name(ARG_TOKEN_COMMAND)
+        .long("token-command")
+        .value_name("COMMAND")
+        .help("Command printing a Bearer token on its stdout (e.g. 'gcloud auth print-access-token')")
+        .env("TOKEN_COMMAND")
+        .global(true)
         .conflicts_with(ARG_BEARER_TOKEN);
 
+    let token_cache_ttl = Arg::with_name(ARG_TOKEN_CACHE_TTL)
+        .long("token-cache-ttl")
+        .value_name("SECONDS")
+        .help("Caches the token printed by the token command for the given time instead of running it for every request")
+        .global(true)
+        .validator(arg_validators::unsigned_int);
+
+    let oauth2_token_url = Arg::with_name(ARG_OAUTH2_TOKEN_URL)
+        .long("oauth2-token-url")
+        .value_name("URL")
+        .help("Token endpoint to acquire a Bearer token from using OAuth2 client credentials")
+        .env("OAUTH2_TOKEN_URL")
+        .global(true)
+        .conflicts_with_all(&[ARG_ZIGN, ARG_TOKEN_COMMAND, ARG_BEARER_TOKEN]);
+
+    let oauth2_client_id = Arg::with_name(ARG_OAUTH2_CLIENT_ID)
+        .long("oauth2-client-id")
+        .value_name("CLIENT_ID")
+        .help("OAuth2 client ID (Use '@' prefix to specify a filepath. e.g. '@client-id')")
+        .env("OAUTH2_CLIENT_ID")
+        .global(true);
+
+    let oauth2_client_secret = Arg::with_name(ARG_OAUTH2_CLIENT_SECRET)
+        .long("oauth2-client-secret")
+        .value_name("CLIENT_SECRET")
+        .help("OAuth2 client secret (Use '@' prefix to specify a filepath. e.g. '@client-secret')")
+        .env("OAUTH2_CLIENT_SECRET")
+        .hide_env_values(true)
+        .global(true);
+
+    let oauth2_scope = Arg::with_name(ARG_OAUTH2_SCOPE)
+        .long("oauth2-scope")
+        .value_name("SCOPE")
+        .help("Space-separated scopes to request the OAuth2 token with")
+        .global(true);
+
     let pretty = Arg::with_name(ARG_PRETTY)
         .long("pretty")
         .help("Prints pretty JSON output")
@@ -61,14 +228,73 @@ pub fn global_args() -> Vec<Arg<'static, 'static>> {
         .help("Network timeout for non-streaming operations (in seconds)")
         .global(true)
         .takes_value(true)
-        .default_value("1")
         .validator(arg_validators::unsigned_int);
 
+    let profile = Arg::with_name(ARG_PROFILE)
+        .long("profile")
+        .value_name("PROFILE")
+        .help("Name of the profile of the configuration file to use")
+        .env("NAKACLI_PROFILE")
+        .global(true);
+
+    let flow_id = Arg::with_name(ARG_FLOW_ID)
+        .long("flow-id")
+        .value_name("FLOW_ID")
+        .help("X-Flow-Id header sent with every request to correlate them in the Nakadi logs (default: a generated UUID)")
+        .env("FLOW_ID")
+        .global(true);
+
+    let verbose = Arg::with_name(ARG_VERBOSE)
+        .short("v")
+        .long("verbose")
+        .help("Traces requests and responses on STDERR, with their bodies too if repeated (-vv)")
+        .global(true)
+        .multiple(true)
+        .takes_value(false);
+
+    let output = Arg::with_name(ARG_OUTPUT)
+        .long("output")
+        .value_name("FORMAT")
+        .help("Format of the output (default: json), where table is not supported by the stream commands")
+        .global(true)
+        .takes_value(true)
+        .possible_values(ARG_OUTPUT_VALUES);
+
+    let columns = Arg::with_name(ARG_COLUMNS)
+        .long("columns")
+        .value_name("COLUMNS")
+        .help("Comma-separated fields shown as the columns of the table output (e.g. 'name,options.retention_time'), only supported with --output table")
+        .global(true)
+        .takes_value(true)
+        .use_delimiter(true)
+        .requires(ARG_OUTPUT);
+
+    let error_format = Arg::with_name(ARG_ERROR_FORMAT)
+        .long("error-format")
+        .value_name("FORMAT")
+        .help("Format of failures written to STDERR")
+        .global(true)
+        .takes_value(true)
+        .possible_values(ARG_ERROR_FORMAT_VALUES)
+        .default_value("text");
+
     vec![
         bearer_token,
         nakadi_url,
         zign,
+        token_command,
+        token_cache_ttl,
+        oauth2_token_url,
+        oauth2_client_id,
+        oauth2_client_secret,
+        oauth2_scope,
         pretty,
         network_timeout,
+        profile,
+        flow_id,
+        verbose,
+        output,
+        columns,
+        error_format,
     ]
 }
@@ -5,7 +5,8 @@ use hyper::header;
 use futures::future;
 use futures::Future;
 use futures::Stream;
-use output::{failure_detailed, Failure};
+use futures::stream;
+use output::{failure_detailed, failure_network, failure_response, Failure};
 use server::Authorization;
 use auth;
 use hyper::client::{HttpConnector};
@@ -13,20 +14,59 @@ use hyper_tls::HttpsConnector;
 use hyper::{Response, Client, StatusCode};
 use server::ServerInfo;
 use serde_json::{Value};
+use serde::de::DeserializeOwned;
 use serde_json;
+use url::form_urlencoded;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
 
 pub type HttpClient = Client<HttpsConnector<HttpConnector>>;
 
-/// Builds a request from the given parameters. If bod is provided, the Content-Type header is set to `application/json`.
+header! { (XNakadiStreamId, "X-Nakadi-StreamId") => [String] }
+header! { (XNakadiCursors, "X-Nakadi-Cursors") => [String] }
+header! { (XFlowId, "X-Flow-Id") => [String] }
+
+/// Verbosity at which requests and responses are traced on STDERR, without their bodies
+pub const VERBOSITY_HEADERS: u64 = 1;
+
+/// Verbosity at which the bodies of requests and responses are traced on STDERR too
+pub const VERBOSITY_BODIES: u64 = 2;
+
+thread_local! {
+    static VERBOSITY: Cell<u64> = Cell::new(0);
+}
+
+/// Sets how much of every request and response is traced on STDERR, from not at all (0) to `VERBOSITY_BODIES`
+pub fn set_verbosity(verbosity: u64) {
+    VERBOSITY.with(|current| current.set(verbosity))
+}
+
+fn verbosity() -> u64 {
+    VERBOSITY.with(Cell::get)
+}
+
+/// Appends the URL-encoded query parameters to the given path
+pub fn path_with_query(path: &str, query_params: &[(&str, &str)]) -> String {
+    if query_params.is_empty() {
+        path.to_owned()
+    } else {
+        let query = form_urlencoded::Serializer::new(String::new()).extend_pairs(query_params).finish();
+        format!("{}?{}", path, query)
+    }
+}
+
+/// Builds a request from the given parameters, carrying the flow ID. If bod is provided, the Content-Type header is set to `application/json`.
 pub fn build_request(method: Method, path: &str, server_info: &ServerInfo, body: Option<&Value>) -> Result<Request, Failure> {
 
     let uri = format!("{}{}", server_info.url_base, path).parse().expect("Failed to construct URI for HTTP request");
     let mut request = Request::new(method, uri);
+    request.headers_mut().set(XFlowId(server_info.flow_id.to_owned()));
 
     let bearer_token = match server_info.authorization {
         Authorization::None => None,
         Authorization::BearerToken(token) => Some(token.to_owned()),
-        Authorization::Zign => Some(auth::zign()?)
+        Authorization::TokenCommand(token_command) => Some(auth::token_command(token_command)?),
+        Authorization::OAuth2ClientCredentials(credentials) => Some(auth::oauth2_client_credentials(credentials)?),
     };
 
     if let Some(bearer_token_value) = bearer_token {
@@ -49,13 +89,100 @@ pub fn read_full_resp_body_utf8(response: hyper::Response) -> impl Future<Item=S
     response
         .body()
         .concat2()
-        .map_err(|err| failure_detailed("HTTP error", err))
+        .map_err(|err| failure_network("HTTP error", err))
         .and_then(|chunk| String::from_utf8(chunk.into_iter().collect()).map_err(|err| failure_detailed("UTF-8 decoding failure", err)))
+        .map(|body| {
+            if verbosity() >= VERBOSITY_BODIES {
+                trace_body("<", &body)
+            }
+            body
+        })
 }
 
-/// Executes a `Request` yielding a `Response`
+/// Streams a response body as a sequence of newline-delimited UTF-8 lines (without the trailing newlines).
+pub fn read_lines_utf8(response: hyper::Response) -> impl Stream<Item=String, Error=Failure> {
+    let mut buffer: Vec<u8> = Vec::new();
+    response
+        .body()
+        .map_err(|err| failure_network("Failed to stream HTTP chunks", err))
+        .map(move |chunk| {
+            buffer.extend(chunk);
+            let mut lines = Vec::new();
+            while let Some(newline_index) = buffer.iter().position(|&byte| byte == b'\n') {
+                let mut line: Vec<u8> = buffer.drain(..newline_index + 1).collect();
+                line.pop();
+                lines.push(line);
+            }
+            stream::iter_ok(lines)
+        })
+        .flatten()
+        .and_then(|line| String::from_utf8(line).map_err(|err| failure_detailed("UTF-8 decoding failure", err)))
+        .map(|line| {
+            if verbosity() >= VERBOSITY_BODIES {
+                trace_body("<", &line)
+            }
+            line
+        })
+}
+
+/// Executes a `Request` yielding a `Response`, tracing both on STDERR depending on the verbosity
 pub fn execute_request(http_client: &HttpClient, request: Request) -> impl Future<Item=Response, Error=Failure> {
-    http_client.request(request).map_err(|err| failure_detailed("Sending HTTP request failed", err))
+    if verbosity() < VERBOSITY_HEADERS {
+        return future::Either::A(http_client.request(request).map_err(|err| failure_network("Sending HTTP request failed", err)))
+    }
+
+    let http_client = http_client.clone();
+    let (method, uri, version, headers, body) = request.deconstruct();
+    let traced = body.concat2()
+        .map_err(|err| failure_detailed("Failed to read the request body", err))
+        .and_then(move |body| {
+            eprintln!("> {} {} {}", method, uri, version);
+            trace_headers(">", &headers);
+            eprintln!("> [{} bytes]", body.len());
+            if verbosity() >= VERBOSITY_BODIES && !body.is_empty() {
+                trace_body(">", &String::from_utf8_lossy(&body))
+            }
+
+            let mut request = Request::new(method, uri);
+            request.set_version(version);
+            *request.headers_mut() = headers;
+            if !body.is_empty() {
+                request.set_body(body);
+            }
+
+            let started = Instant::now();
+            http_client.request(request)
+                .map_err(|err| failure_network("Sending HTTP request failed", err))
+                .map(move |response| {
+                    eprintln!("< {} {} [{} ms]", response.version(), response.status(), millis(started.elapsed()));
+                    trace_headers("<", response.headers());
+                    response
+                })
+        });
+    future::Either::B(traced)
+}
+
+/// Writes every header on its own line, without the credentials of the Authorization header
+fn trace_headers(prefix: &str, headers: &header::Headers) {
+    for header in headers.iter() {
+        if header.name().eq_ignore_ascii_case("Authorization") {
+            let value = header.value_string();
+            let scheme = value.split_whitespace().next().unwrap_or("");
+            eprintln!("{} {}: {} [REDACTED]", prefix, header.name(), scheme);
+        } else {
+            eprintln!("{} {}: {}", prefix, header.name(), header.value_string());
+        }
+    }
+}
+
+fn trace_body(prefix: &str, body: &str) {
+    for line in body.lines() {
+        eprintln!("{} {}", prefix, line);
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_nanos()) / 1_000_000
 }
 
 /// Executes an HTTP request with the given paramters, and returns the [[StatusCode]] and full body of the response
@@ -71,6 +198,26 @@ pub fn execute_and_read_full_resp_body_utf8<'a>(
         .and_then(|resp| { let status = resp.status(); read_full_resp_body_utf8(resp).map(move |v| (status, v))})
 }
 
+/// Fails with the status and body of a response which didn't have the expected status
+pub fn unexpected_response<T>(response: Response) -> impl Future<Item=T, Error=Failure> {
+    let status = response.status();
+    read_full_resp_body_utf8(response).and_then(move |body| Err(failure_response(&format!("Unexpected response ({})", status), status, body)))
+}
+
+/// Executes a GET request to `path` and JSON-decodes the response body, failing on any status code other than 200 OK
+pub fn get_json<'a, T: DeserializeOwned + 'a>(http_client: &'a HttpClient, path: &str, server_info: &'a ServerInfo<'a>) -> impl Future<Item=T, Error=Failure> + 'a {
+    future::result(build_request(Method::Get, path, server_info, None))
+        .and_then(move |r| execute_request(http_client, r))
+        .and_then(|resp| { let status = resp.status(); read_full_resp_body_utf8(resp).map(move |v| (status, v))})
+        .and_then(|(status, body)| {
+            if status == StatusCode::Ok {
+                serde_json::from_str(&body).map_err(|err| failure_detailed("Failed to decode the response body", err))
+            } else {
+                Err(failure_response(&format!("Unexpected response ({})", status), status, body))
+            }
+        })
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -85,7 +232,8 @@ mod tests {
         let arb_method = Method::Put;
         let arb_path = "/fjwlfkjewg/wgwe";
         let arb_authorization = Authorization::BearerToken("sup");
-        let arb_server_info = ServerInfo { url_base: "http://hostname", authorization: arb_authorization };
+        let arb_flow_id = "dwqe-wfwe-dfew";
+        let arb_server_info = ServerInfo { url_base: "http://hostname", authorization: arb_authorization, flow_id: arb_flow_id };
         let arb_body = json!({"name": "John Doe", "age": 43, "phones": [ "+44 1234567", "+44 2345678" ] });
 
 
@@ -94,6 +242,7 @@ mod tests {
         assert_eq!(arb_path, json_request.path());
         assert_eq!(&arb_method, json_request.method());
         assert_eq!(Some(&header::ContentType::json()), json_request.headers().get::<header::ContentType>());
+        assert_eq!(Some(&XFlowId(arb_flow_id.to_owned())), json_request.headers().get::<XFlowId>());
 
         match arb_authorization {
             Authorization::None => assert!(!json_request.headers().has::<header::Authorization<header::Bearer>>()),
@@ -113,4 +262,13 @@ mod tests {
         };
         assert_eq!(arb_body, request_body)
     }
+
+    #[test]
+    fn builds_path_with_query() {
+        assert_eq!("/subscriptions", path_with_query("/subscriptions", &[]));
+        assert_eq!(
+            "/subscriptions?owning_application=app+x&event_type=a%2Fb&event_type=c",
+            path_with_query("/subscriptions", &[("owning_application", "app x"), ("event_type", "a/b"), ("event_type", "c")])
+        );
+    }
 }
@@ -1,15 +1,57 @@
 use std::fs::File;
+use std::io;
 use std::io::prelude::*;
+use std::io::BufReader;
+use std::cell::RefCell;
 
+/// The `@FILEPATH` which reads from STDIN instead of a file
+const STDIN_PATH: &str = "-";
 
-/// Long arguments can be specified with the @FILEPATH to make the content of a file instead
+thread_local! {
+    /// STDIN can only be read once, so it's kept for when the same long argument is read again (e.g. after clap validated it)
+    static STDIN_CONTENT: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Long arguments can be specified with the @FILEPATH to make the content of a file instead, or with @- to read STDIN
 pub fn long_argument(value: &str) -> Result<String, String> {
     if value.starts_with('@') {
-        let mut content = String::new();
         let path = &value[1..];
+        if path == STDIN_PATH {
+            return read_stdin()
+        }
+        let mut content = String::new();
         let mut file = File::open(path).map_err(|err| format!("Could not open file {}: {}", path, err))?;
         file.read_to_string(&mut content).map(|_| content).map_err(|err| format!("Could not read from the file {}: {}", path, err))
     } else {
         Ok(value.to_owned())
     }
 }
+
+/// Reads the lines of a long argument one at a time, without holding all of its content in memory
+pub fn long_argument_lines(value: &str) -> Result<Box<dyn Iterator<Item=Result<String, String>>>, String> {
+    if value.starts_with('@') {
+        let path = value[1..].to_owned();
+        if path == STDIN_PATH {
+            let lines = BufReader::new(io::stdin()).lines().map(|line| line.map_err(|err| format!("Could not read from STDIN: {}", err)));
+            return Ok(Box::new(lines))
+        }
+        let file = File::open(&path).map_err(|err| format!("Could not open file {}: {}", path, err))?;
+        let lines = BufReader::new(file).lines().map(move |line| line.map_err(|err| format!("Could not read from the file {}: {}", path, err)));
+        Ok(Box::new(lines))
+    } else {
+        let lines: Vec<Result<String, String>> = value.lines().map(|line| Ok(line.to_owned())).collect();
+        Ok(Box::new(lines.into_iter()))
+    }
+}
+
+fn read_stdin() -> Result<String, String> {
+    STDIN_CONTENT.with(|cached| {
+        if let Some(ref content) = *cached.borrow() {
+            return Ok(content.clone())
+        }
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content).map_err(|err| format!("Could not read from STDIN: {}", err))?;
+        *cached.borrow_mut() = Some(content.clone());
+        Ok(content)
+    })
+}
@@ -0,0 +1,94 @@
+use serde_json::{Value, to_string};
+use ansi_term::Colour;
+use std::fmt::{Display, Formatter};
+use std::fmt;
+
+/// A single structural difference between two JSON values, located by a JSON Pointer
+#[derive(Debug, PartialEq)]
+pub enum Difference {
+    Added { path: String, value: Value },
+    Removed { path: String, value: Value },
+    Changed { path: String, old: Value, new: Value },
+}
+
+/// Computes the structural differences turning `old` into `new`
+pub fn diff(old: &Value, new: &Value) -> Vec<Difference> {
+    let mut differences = vec![];
+    diff_at("", old, new, &mut differences);
+    differences
+}
+
+fn diff_at(path: &str, old: &Value, new: &Value, differences: &mut Vec<Difference>) {
+    match (old, new) {
+        (&Value::Object(ref old_fields), &Value::Object(ref new_fields)) => {
+            for (key, old_value) in old_fields {
+                let field_path = format!("{}/{}", path, escape(key));
+                match new_fields.get(key) {
+                    Some(new_value) => diff_at(&field_path, old_value, new_value, differences),
+                    None => differences.push(Difference::Removed { path: field_path, value: old_value.clone() }),
+                }
+            }
+            for (key, new_value) in new_fields {
+                if !old_fields.contains_key(key) {
+                    differences.push(Difference::Added { path: format!("{}/{}", path, escape(key)), value: new_value.clone() });
+                }
+            }
+        },
+        (&Value::Array(ref old_items), &Value::Array(ref new_items)) => {
+            for (index, old_item) in old_items.iter().enumerate() {
+                let item_path = format!("{}/{}", path, index);
+                match new_items.get(index) {
+                    Some(new_item) => diff_at(&item_path, old_item, new_item, differences),
+                    None => differences.push(Difference::Removed { path: item_path, value: old_item.clone() }),
+                }
+            }
+            for (index, new_item) in new_items.iter().enumerate().skip(old_items.len()) {
+                differences.push(Difference::Added { path: format!("{}/{}", path, index), value: new_item.clone() });
+            }
+        },
+        _ if old != new => differences.push(Difference::Changed { path: path.to_owned(), old: old.clone(), new: new.clone() }),
+        _ => (),
+    }
+}
+
+/// Escapes a key to be used as a JSON Pointer reference token
+pub(crate) fn escape(key: &str) -> String {
+    key.replace("~", "~0").replace("/", "~1")
+}
+
+fn show(value: &Value) -> String {
+    to_string(value).expect("Failed to serialize a JSON value")
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Difference::Added { ref path, ref value } =>
+                write!(f, "{}", Colour::Green.paint(format!("+ {}: {}", path, show(value)))),
+            Difference::Removed { ref path, ref value } =>
+                write!(f, "{}", Colour::Red.paint(format!("- {}: {}", path, show(value)))),
+            Difference::Changed { ref path, ref old, ref new } =>
+                write!(f, "{}", Colour::Yellow.paint(format!("~ {}: {} -> {}", path, show(old), show(new)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn diffs_nested_values() {
+
+        let old = json!({"type": "object", "properties": {"id": {"type": "string"}, "quantity": {"type": "number"}}, "required": ["id"]});
+        let new = json!({"type": "object", "properties": {"id": {"type": "string"}, "quantity": {"type": "integer"}, "a/b": {}}, "required": ["id", "quantity"]});
+
+        assert_eq!(diff(&old, &new), vec![
+            Difference::Changed { path: "/properties/quantity/type".to_owned(), old: json!("number"), new: json!("integer") },
+            Difference::Added { path: "/properties/a~1b".to_owned(), value: json!({}) },
+            Difference::Added { path: "/required/1".to_owned(), value: json!("quantity") },
+        ]);
+        assert!(diff(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+use serde_json::{Value, Map};
+use regex::Regex;
+use json_diff::escape;
+
+/// A violation of a JSON Schema, located by a JSON Pointer into the validated value
+#[derive(Debug, PartialEq)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+/// Validates `value` against a JSON Schema (draft 4), collecting every violation found.
+///
+/// Only references local to the schema (e.g. `#/definitions/item`) are followed, and `format` is not checked.
+pub fn validate(schema: &Value, value: &Value) -> Vec<ValidationError> {
+    let mut errors = vec![];
+    Validator { root: schema }.validate(schema, value, "", &mut errors);
+    errors
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+}
+
+impl<'a> Validator<'a> {
+
+    fn validate(&self, schema: &Value, value: &Value, path: &str, errors: &mut Vec<ValidationError>) {
+        let schema = match schema.as_object() {
+            Some(schema) => schema,
+            None => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match self.resolve(reference) {
+                Some(referenced) => self.validate(referenced, value, path, errors),
+                None => errors.push(ValidationError { path: path.to_owned(), message: format!("Unsupported schema reference {}", reference) }),
+            }
+            return
+        }
+
+        let mut messages = vec![];
+
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match *types {
+                Value::String(ref single) => vec![single.as_str()],
+                Value::Array(ref multiple) => multiple.iter().filter_map(Value::as_str).collect(),
+                _ => vec![],
+            };
+            if !types.is_empty() && !types.iter().any(|t| is_type(value, t)) {
+                messages.push(format!("Expected {} but found {}", types.join(" or "), type_of(value)));
+            }
+        }
+
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            if !values.contains(value) {
+                messages.push(format!("Value {} is not one of {}", value, Value::Array(values.clone())));
+            }
+        }
+
+        match *value {
+            Value::Number(_) => validate_number(schema, value.as_f64().unwrap_or(0.0), &mut messages),
+            Value::String(ref string) => validate_string(schema, string, &mut messages),
+            _ => (),
+        }
+
+        errors.extend(messages.into_iter().map(|message| ValidationError { path: path.to_owned(), message }));
+
+        match *value {
+            Value::Object(ref fields) => self.validate_object(schema, fields, path, errors),
+            Value::Array(ref items) => self.validate_array(schema, items, path, errors),
+            _ => (),
+        }
+
+        self.validate_combinations(schema, value, path, errors);
+    }
+
+    fn validate_object(&self, schema: &Map<String, Value>, fields: &Map<String, Value>, path: &str, errors: &mut Vec<ValidationError>) {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    errors.push(ValidationError { path: path.to_owned(), message: format!("Missing required property {}", name) });
+                }
+            }
+        }
+        if let Some(min_properties) = schema.get("minProperties").and_then(Value::as_u64) {
+            if (fields.len() as u64) < min_properties {
+                errors.push(ValidationError { path: path.to_owned(), message: format!("Object has less than {} properties", min_properties) });
+            }
+        }
+        if let Some(max_properties) = schema.get("maxProperties").and_then(Value::as_u64) {
+            if fields.len() as u64 > max_properties {
+                errors.push(ValidationError { path: path.to_owned(), message: format!("Object has more than {} properties", max_properties) });
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let pattern_properties: Vec<(Regex, &Value)> = schema.get("patternProperties").and_then(Value::as_object)
+            .map(|patterns| patterns.iter().filter_map(|(pattern, schema)| Regex::new(pattern).ok().map(|regex| (regex, schema))).collect())
+            .unwrap_or_default();
+
+        for (name, field) in fields {
+            let field_path = format!("{}/{}", path, escape(name));
+            let mut matched = false;
+            if let Some(field_schema) = properties.and_then(|properties| properties.get(name)) {
+                matched = true;
+                self.validate(field_schema, field, &field_path, errors);
+            }
+            for &(ref regex, field_schema) in &pattern_properties {
+                if regex.is_match(name) {
+                    matched = true;
+                    self.validate(field_schema, field, &field_path, errors);
+                }
+            }
+            if !matched {
+                match schema.get("additionalProperties") {
+                    Some(&Value::Bool(false)) =>
+                        errors.push(ValidationError { path: field_path, message: "Additional property is not allowed".to_owned() }),
+                    Some(additional @ &Value::Object(_)) => self.validate(additional, field, &field_path, errors),
+                    _ => (),
+                }
+            }
+        }
+
+        if let Some(dependencies) = schema.get("dependencies").and_then(Value::as_object) {
+            for (name, dependency) in dependencies.iter().filter(|&(name, _)| fields.contains_key(name)) {
+                match *dependency {
+                    Value::Array(ref names) => for required in names.iter().filter_map(Value::as_str) {
+                        if !fields.contains_key(required) {
+                            errors.push(ValidationError { path: path.to_owned(), message: format!("Property {} requires property {}", name, required) });
+                        }
+                    },
+                    _ => self.validate(dependency, &Value::Object(fields.clone()), path, errors),
+                }
+            }
+        }
+    }
+
+    fn validate_array(&self, schema: &Map<String, Value>, items: &[Value], path: &str, errors: &mut Vec<ValidationError>) {
+        if let Some(min_items) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min_items {
+                errors.push(ValidationError { path: path.to_owned(), message: format!("Array has less than {} items", min_items) });
+            }
+        }
+        if let Some(max_items) = schema.get("maxItems").and_then(Value::as_u64) {
+            if items.len() as u64 > max_items {
+                errors.push(ValidationError { path: path.to_owned(), message: format!("Array has more than {} items", max_items) });
+            }
+        }
+        if schema.get("uniqueItems").and_then(Value::as_bool).unwrap_or(false) {
+            let duplicated = items.iter().enumerate().any(|(index, item)| items[index + 1..].contains(item));
+            if duplicated {
+                errors.push(ValidationError { path: path.to_owned(), message: "Array items are not unique".to_owned() });
+            }
+        }
+
+        match schema.get("items") {
+            Some(item_schema @ &Value::Object(_)) => for (index, item) in items.iter().enumerate() {
+                self.validate(item_schema, item, &format!("{}/{}", path, index), errors);
+            },
+            Some(&Value::Array(ref item_schemas)) => for (index, item) in items.iter().enumerate() {
+                let item_path = format!("{}/{}", path, index);
+                match (item_schemas.get(index), schema.get("additionalItems")) {
+                    (Some(item_schema), _) => self.validate(item_schema, item, &item_path, errors),
+                    (None, Some(&Value::Bool(false))) =>
+                        errors.push(ValidationError { path: item_path, message: "Additional item is not allowed".to_owned() }),
+                    (None, Some(additional @ &Value::Object(_))) => self.validate(additional, item, &item_path, errors),
+                    (None, _) => (),
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn validate_combinations(&self, schema: &Map<String, Value>, value: &Value, path: &str, errors: &mut Vec<ValidationError>) {
+        let matches = |subschema: &Value| validate_with_root(self.root, subschema, value).is_empty();
+
+        if let Some(subschemas) = schema.get("allOf").and_then(Value::as_array) {
+            for subschema in subschemas {
+                self.validate(subschema, value, path, errors);
+            }
+        }
+        if let Some(subschemas) = schema.get("anyOf").and_then(Value::as_array) {
+            if !subschemas.iter().any(matches) {
+                errors.push(ValidationError { path: path.to_owned(), message: "Value does not match any of the schemas of anyOf".to_owned() });
+            }
+        }
+        if let Some(subschemas) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = subschemas.iter().filter(|subschema| matches(subschema)).count();
+            if matching != 1 {
+                errors.push(ValidationError { path: path.to_owned(), message: format!("Value matches {} of the schemas of oneOf instead of exactly one", matching) });
+            }
+        }
+        if let Some(subschema) = schema.get("not") {
+            if matches(subschema) {
+                errors.push(ValidationError { path: path.to_owned(), message: "Value matches the schema of not".to_owned() });
+            }
+        }
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        if reference == "#" {
+            Some(self.root)
+        } else if reference.starts_with("#/") {
+            self.root.pointer(&reference[1..])
+        } else {
+            None
+        }
+    }
+}
+
+fn validate_number(schema: &Map<String, Value>, number: f64, messages: &mut Vec<String>) {
+    let exclusive = |keyword: &str| schema.get(keyword).and_then(Value::as_bool).unwrap_or(false);
+    if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+        if number < minimum || (exclusive("exclusiveMinimum") && number == minimum) {
+            messages.push(format!("Value {} is less than the minimum of {}", number, minimum));
+        }
+    }
+    if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+        if number > maximum || (exclusive("exclusiveMaximum") && number == maximum) {
+            messages.push(format!("Value {} is greater than the maximum of {}", number, maximum));
+        }
+    }
+    if let Some(multiple_of) = schema.get("multipleOf").and_then(Value::as_f64) {
+        if !is_multiple_of(number, multiple_of) {
+            messages.push(format!("Value {} is not a multiple of {}", number, multiple_of));
+        }
+    }
+}
+
+/// Exact when both numbers are integers, otherwise tolerant of the rounding errors of decimal fractions (e.g. 0.3 / 0.1)
+fn is_multiple_of(number: f64, multiple_of: f64) -> bool {
+    if number.fract() == 0.0 && multiple_of.fract() == 0.0 {
+        return number % multiple_of == 0.0
+    }
+    let quotient = number / multiple_of;
+    (quotient - quotient.round()).abs() <= 1e-9 * quotient.abs().max(1.0)
+}
+
+fn validate_string(schema: &Map<String, Value>, string: &str, messages: &mut Vec<String>) {
+    let length = string.chars().count() as u64;
+    if let Some(min_length) = schema.get("minLength").and_then(Value::as_u64) {
+        if length < min_length {
+            messages.push(format!("String is shorter than {} characters", min_length));
+        }
+    }
+    if let Some(max_length) = schema.get("maxLength").and_then(Value::as_u64) {
+        if length > max_length {
+            messages.push(format!("String is longer than {} characters", max_length));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        match Regex::new(pattern) {
+            Ok(ref regex) if !regex.is_match(string) => messages.push(format!("String does not match the pattern {}", pattern)),
+            Ok(_) => (),
+            Err(_) => messages.push(format!("Invalid pattern {} in schema", pattern)),
+        }
+    }
+}
+
+fn validate_with_root(root: &Value, schema: &Value, value: &Value) -> Vec<ValidationError> {
+    let mut errors = vec![];
+    Validator { root }.validate(schema, value, "", &mut errors);
+    errors
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("integer", &Value::Number(ref number)) => number.is_i64() || number.is_u64() || number.as_f64().map_or(false, |n| n.fract() == 0.0),
+        (name, value) => type_of(value) == name,
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match *value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn collects_every_violation() {
+
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "quantity"],
+            "properties": {
+                "id": {"type": "string", "pattern": "^[a-z]+$"},
+                "quantity": {"type": "integer", "minimum": 1},
+                "tags": {"type": "array", "items": {"$ref": "#/definitions/tag"}}
+            },
+            "additionalProperties": false,
+            "definitions": {"tag": {"enum": ["new", "used"]}}
+        });
+
+        assert!(validate(&schema, &json!({"id": "abc", "quantity": 2, "tags": ["new"]})).is_empty());
+
+        let errors = validate(&schema, &json!({"id": "ABC", "quantity": 0.5, "tags": ["new", "old"], "extra": true}));
+        let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+        assert_eq!(paths, vec!["/extra", "/id", "/quantity", "/quantity", "/tags/1"]);
+
+        assert_eq!(validate(&schema, &json!({"id": "abc"})), vec![
+            ValidationError { path: "".to_owned(), message: "Missing required property quantity".to_owned() },
+        ]);
+    }
+
+    #[test]
+    fn checks_multiples_of_decimal_fractions() {
+
+        let schema = json!({"type": "number", "multipleOf": 0.1});
+        assert!(validate(&schema, &json!(0.3)).is_empty());
+        assert!(validate(&schema, &json!(4.2)).is_empty());
+        assert_eq!(validate(&schema, &json!(0.35)).len(), 1);
+
+        let schema = json!({"type": "integer", "multipleOf": 3});
+        assert!(validate(&schema, &json!(9)).is_empty());
+        assert_eq!(validate(&schema, &json!(10)).len(), 1);
+    }
+}
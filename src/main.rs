@@ -2,13 +2,21 @@
 
 extern crate futures;
 extern crate tokio_core;
-extern crate hyper;
 extern crate hyper_tls;
 extern crate ansi_term;
 extern crate clap;
 extern crate serde;
 extern crate uuid;
 extern crate chrono;
+extern crate url;
+extern crate regex;
+extern crate rand;
+extern crate toml;
+extern crate dirs;
+extern crate serde_yaml;
+
+#[macro_use]
+extern crate hyper;
 
 #[macro_use]
 extern crate serde_json;
@@ -27,11 +35,35 @@ mod command_eventtype;
 mod command_eventtype_list;
 mod command_eventtype_create;
 mod command_eventtype_delete;
+mod command_eventtype_partitions;
+mod command_eventtype_update;
+mod command_eventtype_get;
+mod command_eventtype_schemas;
+mod command_eventtype_schema;
+mod command_subscription;
+mod command_subscription_create;
+mod command_subscription_stream;
+mod command_subscription_list;
+mod command_subscription_get;
+mod command_subscription_delete;
+mod command_subscription_stats;
+mod command_subscription_reset_cursors;
+mod command_subscription_cursors;
 mod auth;
 mod output;
 mod global;
 mod input;
 mod arg_validators;
+mod partition;
+mod cursor;
+mod json_diff;
+mod json_schema;
+mod batch_item;
+mod config;
+mod command_config;
+mod command_config_list;
+mod command_config_show;
+mod command_config_set;
 
 use clap::{App, AppSettings};
 use app::Application;
@@ -43,11 +75,27 @@ fn main() {
         .args(global::global_args().as_slice())
         .subcommand(command_metrics::sub_command())
         .subcommand(command_event::sub_command())
-        .subcommand(command_eventtype::sub_command());
+        .subcommand(command_eventtype::sub_command())
+        .subcommand(command_subscription::sub_command())
+        .subcommand(command_config::sub_command());
 
     let matches = app.get_matches();
 
-    let global_params = global::extract_global_params(&matches);
+    output::set_error_format(global::error_format(&matches));
+
+    if let Some(matches) = matches.subcommand_matches(command_config::NAME) {
+        command_config::run(matches)
+    }
+
+    let global_params = match global::extract_global_params(&matches) {
+        Ok(global_params) => global_params,
+        Err(err) => output::die_failure(err),
+    };
+
+    output::set_pretty_failures(global_params.pretty);
+    output::set_flow_id(&global_params.flow_id);
+    http::set_verbosity(global_params.verbosity);
+    output::set_output_format(global_params.output_format, global_params.columns.clone());
 
     let mut application = Application::new(&global_params);
 
@@ -57,6 +105,8 @@ fn main() {
         command_event::run(&mut application, &global_params, matches)
     } else if let Some(matches) = matches.subcommand_matches(command_eventtype::NAME) {
         command_eventtype::run(&mut application, &global_params, matches)
+    } else if let Some(matches) = matches.subcommand_matches(command_subscription::NAME) {
+        command_subscription::run(&mut application, &global_params, matches)
     } else {
         panic!("No command matched! Should have been caught by clap")
     }
@@ -1,16 +1,94 @@
 
 use serde_json;
+use serde_yaml;
 use serde_json::{Value, to_string_pretty, from_str, to_string};
 use hyper::StatusCode;
 use ansi_term::Colour;
 use std::fmt::{Display, Formatter};
 use std::process::exit;
 use std::fmt;
+use std::cell::{Cell, RefCell};
+use json_diff::escape;
 
-/// Exits the application with failure
+thread_local! {
+    /// Failures can happen anywhere, so the selected format is kept for when the application dies
+    static ERROR_FORMAT: Cell<ErrorFormat> = Cell::new(ErrorFormat::Text);
+    static PRETTY_FAILURES: Cell<bool> = Cell::new(false);
+    static FLOW_ID: RefCell<Option<String>> = RefCell::new(None);
+    static OUTPUT_FORMAT: Cell<OutputFormat> = Cell::new(OutputFormat::Json);
+    static COLUMNS: RefCell<Option<Vec<String>>> = RefCell::new(None);
+}
+
+/// The format values are printed to STDOUT in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    /// One JSON value per line, with top-level arrays flattened to their elements
+    Ndjson,
+    Yaml,
+    /// Objects as rows of a table, whose columns are either selected or all the fields of the objects
+    Table,
+}
+
+/// Selects the format of the output for the rest of the execution, along with the columns of tables
+pub fn set_output_format(output_format: OutputFormat, columns: Option<Vec<String>>) {
+    OUTPUT_FORMAT.with(|format| format.set(output_format));
+    COLUMNS.with(|current| *current.borrow_mut() = columns);
+}
+
+/// Exits with failure if the selected format can't be printed one event at a time, as streams do
+pub fn require_streamable_output_format() {
+    if OUTPUT_FORMAT.with(Cell::get) == OutputFormat::Table {
+        die_failure(failure("--output table is not supported by streams, which print every event as it arrives"))
+    }
+}
+
+/// The format failures are written to STDERR in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+/// Selects the format of failures for the rest of the execution
+pub fn set_error_format(error_format: ErrorFormat) {
+    ERROR_FORMAT.with(|format| format.set(error_format))
+}
+
+/// Makes failures be written across multiple lines for easier human readability
+pub fn set_pretty_failures(pretty: bool) {
+    PRETTY_FAILURES.with(|pretty_failures| pretty_failures.set(pretty))
+}
+
+/// Sets the flow ID of the requests, so that failures can be correlated with the Nakadi logs
+pub fn set_flow_id(flow_id: &str) {
+    FLOW_ID.with(|current| *current.borrow_mut() = Some(flow_id.to_owned()))
+}
+
+/// Exits the application with failure, using the exit code of its kind
 pub fn die_failure(failure: Failure) -> ! {
-    eprintln!("{}", failure);
-    exit(1)
+    let code = failure.kind().exit_code();
+    let flow_id = FLOW_ID.with(|flow_id| flow_id.borrow().clone());
+    match ERROR_FORMAT.with(Cell::get) {
+        ErrorFormat::Text => {
+            if PRETTY_FAILURES.with(Cell::get) {
+                eprintln!("{:#}", failure)
+            } else {
+                eprintln!("{}", failure)
+            }
+            if let Some(flow_id) = flow_id {
+                eprintln!("Flow ID: {}", flow_id)
+            }
+        },
+        ErrorFormat::Json => {
+            let mut json = failure.to_json(code);
+            if let Some(flow_id) = flow_id {
+                json["flow_id"] = json!(flow_id);
+            }
+            eprintln!("{}", to_string(&json).expect("Failed to serialize a JSON value"))
+        },
+    }
+    exit(code)
 }
 
 /// Exits the application with success
@@ -20,19 +98,20 @@ pub fn die_success() -> ! {
 
 /// Prints operation result from the Nakadi server, then exits either with success or failure based on the `expected_status_code`.
 pub fn final_result(result: Result<(StatusCode, String), Failure>, expected_status_code: StatusCode, pretty: bool) {
+    final_result_any(result, &[expected_status_code], pretty)
+}
+
+/// Same as `final_result`, except that any of the `expected_status_codes` is considered a success.
+pub fn final_result_any(result: Result<(StatusCode, String), Failure>, expected_status_codes: &[StatusCode], pretty: bool) {
     match result {
-        Ok((status_code, ref output)) if status_code == expected_status_code => {
+        Ok((status_code, ref output)) if expected_status_codes.contains(&status_code) => {
             if !output.is_empty() {
                 print_json(&output, pretty);
             }
             die_success()
         },
         Ok((status_code, output)) => {
-            if pretty {
-                die_failure(failureln(&format!("Unexpected response ({})", status_code), if output.is_empty() { "[No Response Body]".to_owned() } else { pretty_json(&output) }));
-            } else {
-                die_failure(failure_detailed(&format!("Unexpected response ({})", status_code), if output.is_empty() { "[No Response Body]".to_owned() } else { output }));
-            }
+            die_failure(failure_response(&format!("Unexpected response ({})", status_code), status_code, output))
         }
         Err(err) => {
             die_failure(err);
@@ -40,44 +119,336 @@ pub fn final_result(result: Result<(StatusCode, String), Failure>, expected_stat
     }
 }
 
-/// Prints a JSON value encoded as a String
+/// Prints a JSON value encoded as a String, in the selected output format if it can be decoded
 pub fn print_json(result: &str, pretty: bool) {
-    if pretty {
-        println!("{}", pretty_json(result))
-    } else {
-        println!("{}", result)
+    match (OUTPUT_FORMAT.with(Cell::get), from_str::<Value>(result)) {
+        (OutputFormat::Json, _) | (_, Err(_)) => {
+            if pretty {
+                println!("{}", pretty_json(result))
+            } else {
+                println!("{}", result)
+            }
+        },
+        (_, Ok(value)) => print_json_value(&value, pretty),
     }
 }
 
-/// Prints a JSON value
+/// Prints a JSON value in the selected output format
 pub fn print_json_value(value: &Value, pretty: bool) {
-    if pretty {
-        println!("{}", to_string_pretty(value).expect("Failed to serialize a JSON value"))
-    } else {
-        println!("{}", to_string(value).expect("Failed to serialize a JSON value"))
+    match OUTPUT_FORMAT.with(Cell::get) {
+        OutputFormat::Json if pretty => println!("{}", to_string_pretty(value).expect("Failed to serialize a JSON value")),
+        OutputFormat::Json => println!("{}", to_string(value).expect("Failed to serialize a JSON value")),
+        OutputFormat::Ndjson => {
+            let values = match *value {
+                Value::Array(ref items) => items.iter().collect(),
+                ref other => vec![other],
+            };
+            for value in values {
+                println!("{}", to_string(value).expect("Failed to serialize a JSON value"))
+            }
+        },
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(value).expect("Failed to serialize a value as YAML")),
+        OutputFormat::Table => print_value_table(value),
     }
 }
 
+/// Prints an object, or an array of them, as a table with a column for each of the selected fields.
+/// Fields of nested objects can be selected using dots, e.g. `options.retention_time`.
+fn print_value_table(value: &Value) {
+    let rows: Vec<&Value> = match *value {
+        Value::Array(ref items) => items.iter().collect(),
+        Value::Object(_) => vec![value],
+        Value::String(ref text) => {
+            println!("{}", text);
+            return
+        },
+        ref other => {
+            println!("{}", other);
+            return
+        },
+    };
+
+    let columns = COLUMNS.with(|columns| columns.borrow().clone()).unwrap_or_else(|| {
+        let mut columns: Vec<String> = Vec::new();
+        for row in &rows {
+            if let Value::Object(ref fields) = **row {
+                for name in fields.keys() {
+                    if !columns.contains(name) {
+                        columns.push(name.clone());
+                    }
+                }
+            }
+        }
+        columns
+    });
+
+    let headers: Vec<String> = columns.iter().map(|column| column.to_uppercase().replace('_', " ")).collect();
+    let cells: Vec<Vec<String>> = rows.iter().map(|row| {
+        columns.iter().map(|column| {
+            let pointer: String = column.split('.').map(|key| format!("/{}", escape(key))).collect();
+            match row.pointer(&pointer) {
+                None | Some(&Value::Null) => String::new(),
+                Some(&Value::String(ref text)) => text.clone(),
+                Some(other) => other.to_string(),
+            }
+        }).collect()
+    }).collect();
+
+    print_table(&headers.iter().map(String::as_str).collect::<Vec<&str>>(), &cells)
+}
+
+/// Prints rows as a table of left-aligned columns, preceded by a row of `headers`
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    println!("{}", render_table(headers, rows))
+}
+
+/// Renders rows as the lines of a table, like `print_table` does
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| -> String {
+        let last_index = cells.len().saturating_sub(1);
+        let padded: Vec<String> = cells.iter().zip(widths.iter()).enumerate().map(|(index, (cell, width))| {
+            if index == last_index { cell.to_string() } else { format!("{:width$}", cell, width = width) }
+        }).collect();
+        padded.join("  ")
+    };
+
+    let mut lines = vec![format_row(headers.to_vec())];
+    lines.extend(rows.iter().map(|row| format_row(row.iter().map(|cell| cell.as_str()).collect())));
+    lines.join("\n")
+}
+
 /// Canonical representation of error message
 pub fn failure_detailed<A: Display>(header: &str, detailed: A) -> Failure {
-    Failure { show: format!("{}: {}", Colour::Red.paint(header), detailed) }
+    Failure::General { header: header.to_owned(), detail: Some(detailed.to_string()) }
 }
 
 pub fn failure(message: &str) -> Failure {
-    Failure { show: message.to_owned() }
+    Failure::General { header: message.to_owned(), detail: None }
+}
+
+/// A failure to communicate with a server
+pub fn failure_network<A: Display>(header: &str, detailed: A) -> Failure {
+    Failure::Network { header: header.to_owned(), detail: detailed.to_string() }
 }
 
-pub struct Failure { show: String }
+/// Events that Nakadi did not publish out of `total` events, reported as a table of their `rows`, or as their `items`
+/// with `--error-format json`
+pub fn failure_unpublished(total: usize, headers: &[&str], rows: &[Vec<String>], items: Vec<Value>) -> Failure {
+    Failure::Unpublished {
+        header: format!("{} of {} events were not published", items.len(), total),
+        partial: items.len() < total,
+        report: render_table(headers, rows),
+        items,
+    }
+}
 
-impl Display for Failure {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{}", self.show)
+/// A response with an unexpected status, whose `body` is decoded if it's a problem document
+pub fn failure_response(header: &str, status: StatusCode, body: String) -> Failure {
+    let problem = from_str::<Problem>(&body).ok()
+        .and_then(|problem| if problem.title.is_some() || problem.problem_type.is_some() { Some(Box::new(problem)) } else { None });
+    Failure::Response { header: header.to_owned(), status, body, problem }
+}
+
+/// The kinds of failures, which can be told apart by the exit code of the application:
+///
+/// | Kind                  | Exit code | Cause                                               |
+/// |-----------------------|-----------|-----------------------------------------------------|
+/// | `general`             | 1         | Anything else, e.g. invalid input                   |
+/// | `partially-published` | 2         | Some, but not all, of the events were not published |
+/// | `unpublished`         | 3         | None of the events were published                   |
+/// | `authorization`       | 4         | 401 or 403 responses                                |
+/// | `not-found`           | 5         | 404 responses                                       |
+/// | `validation`          | 6         | 400 or 422 responses                                |
+/// | `conflict`            | 7         | 409 responses                                       |
+/// | `server`              | 8         | 5xx responses                                       |
+/// | `network`             | 9         | Server unreachable, connection lost or timed out    |
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailureKind {
+    General,
+    PartiallyPublished,
+    Unpublished,
+    Authorization,
+    NotFound,
+    Validation,
+    Conflict,
+    Server,
+    Network,
+}
+
+impl FailureKind {
+
+    pub fn of_status(status: StatusCode) -> FailureKind {
+        match status {
+            StatusCode::Unauthorized | StatusCode::Forbidden => FailureKind::Authorization,
+            StatusCode::NotFound => FailureKind::NotFound,
+            StatusCode::BadRequest | StatusCode::UnprocessableEntity => FailureKind::Validation,
+            StatusCode::Conflict => FailureKind::Conflict,
+            status if status.is_server_error() => FailureKind::Server,
+            _ => FailureKind::General,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            FailureKind::General => 1,
+            FailureKind::PartiallyPublished => 2,
+            FailureKind::Unpublished => 3,
+            FailureKind::Authorization => 4,
+            FailureKind::NotFound => 5,
+            FailureKind::Validation => 6,
+            FailureKind::Conflict => 7,
+            FailureKind::Server => 8,
+            FailureKind::Network => 9,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            FailureKind::General => "general",
+            FailureKind::PartiallyPublished => "partially-published",
+            FailureKind::Unpublished => "unpublished",
+            FailureKind::Authorization => "authorization",
+            FailureKind::NotFound => "not-found",
+            FailureKind::Validation => "validation",
+            FailureKind::Conflict => "conflict",
+            FailureKind::Server => "server",
+            FailureKind::Network => "network",
+        }
     }
 }
 
-/// Constructs a `Failure` that spans two lines
-fn failureln<A: Display>(header: &str, detailed: A) -> Failure {
-    Failure { show: format!("{}:\n{}", Colour::Red.paint(header), detailed) }
+/// A problem document (RFC 7807), which Nakadi responds with on failures
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Problem {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub problem_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+impl Problem {
+
+    /// Renders the problem on a single line, e.g. `Not Found (404): EventType "x" does not exist.`
+    fn render_line(&self) -> String {
+        let mut line = Colour::Yellow.paint(self.title.as_ref().or(self.problem_type.as_ref()).map(String::as_str).unwrap_or("")).to_string();
+        if let Some(status) = self.status {
+            line.push_str(&format!(" ({})", status));
+        }
+        if let Some(ref detail) = self.detail {
+            line.push_str(&format!(": {}", detail));
+        }
+        line
+    }
+
+    /// Renders every field of the problem on its own line
+    fn render_lines(&self) -> String {
+        let fields = [
+            ("Title", self.title.clone()),
+            ("Status", self.status.map(|status| status.to_string())),
+            ("Detail", self.detail.clone()),
+            ("Type", self.problem_type.clone()),
+            ("Instance", self.instance.clone()),
+        ];
+        let lines: Vec<String> = fields.iter()
+            .filter_map(|&(label, ref value)| value.as_ref().map(|value| format!("  {} {}", Colour::Yellow.paint(format!("{:9}", format!("{}:", label))), value)))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+pub enum Failure {
+    /// A failure which doesn't involve a server, e.g. invalid input
+    General { header: String, detail: Option<String> },
+    /// The server couldn't be reached, or didn't respond in time
+    Network { header: String, detail: String },
+    /// The server responded with an unexpected status
+    Response { header: String, status: StatusCode, body: String, problem: Option<Box<Problem>> },
+    /// Nakadi did not publish some (`partial`) or all of the events, which are reported as a table and as items
+    Unpublished { header: String, partial: bool, report: String, items: Vec<Value> },
+}
+
+impl Failure {
+
+    pub fn kind(&self) -> FailureKind {
+        match *self {
+            Failure::General { .. } => FailureKind::General,
+            Failure::Network { .. } => FailureKind::Network,
+            Failure::Response { status, .. } => FailureKind::of_status(status),
+            Failure::Unpublished { partial: true, .. } => FailureKind::PartiallyPublished,
+            Failure::Unpublished { partial: false, .. } => FailureKind::Unpublished,
+        }
+    }
+
+    /// The failure as written to STDERR with `--error-format json`
+    fn to_json(&self, exit_code: i32) -> Value {
+        let mut json = json!({ "kind": self.kind().name(), "exit_code": exit_code });
+        match *self {
+            Failure::General { ref header, ref detail } => {
+                json["message"] = json!(header);
+                if let Some(ref detail) = *detail {
+                    json["detail"] = json!(detail);
+                }
+            },
+            Failure::Network { ref header, ref detail } => {
+                json["message"] = json!(header);
+                json["detail"] = json!(detail);
+            },
+            Failure::Response { ref header, status, ref body, ref problem, .. } => {
+                json["message"] = json!(header);
+                json["status"] = json!(u16::from(status));
+                match (problem, body) {
+                    (&Some(ref problem), _) => json["problem"] = json!(problem),
+                    (&None, body) if !body.is_empty() => json["detail"] = json!(body),
+                    _ => (),
+                }
+            },
+            Failure::Unpublished { ref header, ref items, .. } => {
+                json["message"] = json!(header);
+                json["items"] = json!(items);
+            },
+        }
+        json
+    }
+}
+
+/// Responses are displayed across multiple lines with the alternate flag (`{:#}`)
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Failure::General { ref header, detail: None } => write!(f, "{}", header),
+            Failure::General { ref header, detail: Some(ref detail) } => write!(f, "{}: {}", Colour::Red.paint(header.as_str()), detail),
+            Failure::Network { ref header, ref detail } => write!(f, "{}: {}", Colour::Red.paint(header.as_str()), detail),
+            Failure::Response { ref header, problem: Some(ref problem), .. } => {
+                if f.alternate() {
+                    write!(f, "{}:\n{}", Colour::Red.paint(header.as_str()), problem.render_lines())
+                } else {
+                    write!(f, "{}: {}", Colour::Red.paint(header.as_str()), problem.render_line())
+                }
+            },
+            Failure::Unpublished { ref header, ref report, .. } => write!(f, "{}\n{}", report, Colour::Red.paint(header.as_str())),
+            Failure::Response { ref header, ref body, .. } => {
+                let body = if body.is_empty() { "[No Response Body]".to_owned() } else if f.alternate() { pretty_json(body) } else { body.clone() };
+                if f.alternate() {
+                    write!(f, "{}:\n{}", Colour::Red.paint(header.as_str()), body)
+                } else {
+                    write!(f, "{}: {}", Colour::Red.paint(header.as_str()), body)
+                }
+            },
+        }
+    }
 }
 
 fn pretty_json(json: &str) -> String {
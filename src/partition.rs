@@ -0,0 +1,19 @@
+use http;
+use http::HttpClient;
+use futures::Future;
+use server::ServerInfo;
+use output::Failure;
+
+/// A partition of an event type
+#[derive(Deserialize, Debug)]
+pub struct Partition {
+    pub partition: String,
+    pub oldest_available_offset: String,
+    pub newest_available_offset: String,
+    pub unconsumed_events: Option<u64>,
+}
+
+/// Fetches all the partitions of an event type
+pub fn fetch_partitions<'a>(http_client: &'a HttpClient, server_info: &'a ServerInfo<'a>, event_type: &str) -> impl Future<Item=Vec<Partition>, Error=Failure> + 'a {
+    http::get_json(http_client, &format!("/event-types/{}/partitions", event_type), server_info)
+}
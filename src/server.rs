@@ -1,32 +1,38 @@
 use global::GlobalParams;
+use auth::{OAuth2ClientCredentials, TokenCommand};
 
 /// Information about the Nakadi server
 pub struct ServerInfo<'a> {
     pub url_base: &'a str,
-    pub authorization: Authorization<'a>
+    pub authorization: Authorization<'a>,
+    pub flow_id: &'a str,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum Authorization<'a> {
     None,
     BearerToken(&'a str),
-    Zign
+    TokenCommand(&'a TokenCommand),
+    OAuth2ClientCredentials(&'a OAuth2ClientCredentials),
 }
 
 impl <'a> ServerInfo<'a> {
     pub fn from_params(global_params: &'a GlobalParams) -> ServerInfo<'a> {
         let authorization =
-            if global_params.zign {
-                Authorization::Zign
-            } else if let Some(bearer_token) = global_params.bearer_token {
+            if let Some(ref token_command) = global_params.token_command {
+                Authorization::TokenCommand(token_command)
+            } else if let Some(ref bearer_token) = global_params.bearer_token {
                 Authorization::BearerToken(bearer_token)
+            } else if let Some(ref oauth2) = global_params.oauth2 {
+                Authorization::OAuth2ClientCredentials(oauth2)
             } else {
                 Authorization::None
             };
 
         ServerInfo {
-            url_base: global_params.nakadi_url.unwrap_or("http://localhost"),
+            url_base: global_params.nakadi_url.as_ref().map(String::as_str).unwrap_or("http://localhost"),
             authorization,
+            flow_id: &global_params.flow_id,
         }
     }
 }